@@ -0,0 +1,21 @@
+precision mediump float;
+
+#include <lib/blend.glsl>
+
+uniform sampler2D u_backdrop;
+uniform sampler2D u_source;
+/** BlendMode value */
+uniform float u_mode;
+/** CompositeOperator value */
+uniform float u_operator;
+
+varying mediump vec2 v_position;
+
+void main() {
+    gl_FragColor = blend_composite(
+        texture2D(u_backdrop, v_position),
+        texture2D(u_source, v_position),
+        int(u_mode + 0.5),
+        int(u_operator + 0.5)
+    );
+}
@@ -1,9 +1,11 @@
 precision mediump float;
 
+#include <lib/srgb.glsl>
+
 uniform sampler2D u_source;
 
 varying mediump vec2 v_position;
 
 void main() {
-    gl_FragColor = texture2D(u_source, v_position);
+    gl_FragColor = srgb_from_linear(texture2D(u_source, v_position));
 }
@@ -0,0 +1,48 @@
+precision highp float;
+
+/** Colors of stops sampled along the gradient, mirrors Gradient::color_at */
+uniform sampler2D u_stops;
+uniform vec2 u_resolution;
+uniform vec2 u_start;
+/** Direction to the end point divided by the squared length */
+uniform vec2 u_axis;
+/** GradientShape value */
+uniform float u_shape;
+/** GradientSpread value */
+uniform float u_spread;
+
+varying mediump vec2 v_position;
+
+const float PI = 3.14159265;
+
+float gradient_parameter(vec2 point) {
+    vec2 p = point - u_start;
+    float u = dot(u_axis, p);
+    float v = u_axis.x * p.y - u_axis.y * p.x;
+    int shape = int(u_shape + 0.5);
+    if (shape == 0) {
+        return u;
+    } else if (shape == 1) {
+        return length(vec2(u, v));
+    } else if (shape == 2) {
+        return u == 0.0 && v == 0.0 ? 0.0 : fract(atan(v, u) / (2.0 * PI));
+    } else {
+        return abs(u) + abs(v);
+    }
+}
+
+float gradient_spread(float t) {
+    int spread = int(u_spread + 0.5);
+    if (spread == 0) {
+        return clamp(t, 0.0, 1.0);
+    } else if (spread == 1) {
+        return fract(t);
+    } else {
+        return 1.0 - abs(mod(t, 2.0) - 1.0);
+    }
+}
+
+void main() {
+    float t = gradient_spread(gradient_parameter(v_position * u_resolution));
+    gl_FragColor = texture2D(u_stops, vec2((t * 255.0 + 0.5) / 256.0, 0.5));
+}
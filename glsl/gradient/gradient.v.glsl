@@ -0,0 +1,10 @@
+precision highp float;
+
+attribute vec2 a_position;
+
+varying mediump vec2 v_position;
+
+void main() {
+    v_position = a_position;
+    gl_Position = vec4(a_position * 2.0 - 1.0, 0, 1);
+}
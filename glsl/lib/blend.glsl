@@ -0,0 +1,152 @@
+/*
+ * Blend modes and Porter-Duff operators, mirrors src/geometry/blend.rs.
+ * Colors are premultiplied, mode and operator values match BlendMode and CompositeOperator.
+ */
+
+float blend_hard_light(float cb, float cs) {
+    if (cs <= 0.5) {
+        return cb * 2.0 * cs;
+    }
+    float s = 2.0 * cs - 1.0;
+    return cb + s - cb * s;
+}
+
+float blend_soft_light(float cb, float cs) {
+    if (cs <= 0.5) {
+        return cb - (1.0 - 2.0 * cs) * cb * (1.0 - cb);
+    }
+    float d = cb <= 0.25 ? ((16.0 * cb - 12.0) * cb + 4.0) * cb : sqrt(cb);
+    return cb + (2.0 * cs - 1.0) * (d - cb);
+}
+
+float blend_color_dodge(float cb, float cs) {
+    if (cb <= 0.0) {
+        return 0.0;
+    }
+    if (cs >= 1.0) {
+        return 1.0;
+    }
+    return min(1.0, cb / (1.0 - cs));
+}
+
+float blend_color_burn(float cb, float cs) {
+    if (cb >= 1.0) {
+        return 1.0;
+    }
+    if (cs <= 0.0) {
+        return 0.0;
+    }
+    return 1.0 - min(1.0, (1.0 - cb) / cs);
+}
+
+float blend_lum(vec3 c) {
+    return dot(c, vec3(0.3, 0.59, 0.11));
+}
+
+vec3 blend_clip_color(vec3 c) {
+    float l = blend_lum(c);
+    float n = min(min(c.r, c.g), c.b);
+    float x = max(max(c.r, c.g), c.b);
+    if (n < 0.0) {
+        c = l + (c - l) * l / (l - n);
+    }
+    if (x > 1.0) {
+        c = l + (c - l) * (1.0 - l) / (x - l);
+    }
+    return c;
+}
+
+vec3 blend_set_lum(vec3 c, float l) {
+    return blend_clip_color(c + (l - blend_lum(c)));
+}
+
+float blend_sat(vec3 c) {
+    return max(max(c.r, c.g), c.b) - min(min(c.r, c.g), c.b);
+}
+
+vec3 blend_set_sat(vec3 c, float s) {
+    float n = min(min(c.r, c.g), c.b);
+    float x = max(max(c.r, c.g), c.b);
+    if (x > n) {
+        return (c - n) * s / (x - n);
+    }
+    return vec3(0.0);
+}
+
+/** Blending function B(Cb, Cs) on straight colors */
+vec3 blend_mode(vec3 cb, vec3 cs, int mode) {
+    if (mode == 1) {
+        return cb * cs;
+    } else if (mode == 2) {
+        return cb + cs - cb * cs;
+    } else if (mode == 3) {
+        return vec3(blend_hard_light(cs.r, cb.r), blend_hard_light(cs.g, cb.g), blend_hard_light(cs.b, cb.b));
+    } else if (mode == 4) {
+        return min(cb, cs);
+    } else if (mode == 5) {
+        return max(cb, cs);
+    } else if (mode == 6) {
+        return vec3(blend_color_dodge(cb.r, cs.r), blend_color_dodge(cb.g, cs.g), blend_color_dodge(cb.b, cs.b));
+    } else if (mode == 7) {
+        return vec3(blend_color_burn(cb.r, cs.r), blend_color_burn(cb.g, cs.g), blend_color_burn(cb.b, cs.b));
+    } else if (mode == 8) {
+        return vec3(blend_hard_light(cb.r, cs.r), blend_hard_light(cb.g, cs.g), blend_hard_light(cb.b, cs.b));
+    } else if (mode == 9) {
+        return vec3(blend_soft_light(cb.r, cs.r), blend_soft_light(cb.g, cs.g), blend_soft_light(cb.b, cs.b));
+    } else if (mode == 10) {
+        return abs(cb - cs);
+    } else if (mode == 11) {
+        return blend_set_lum(blend_set_sat(cs, blend_sat(cb)), blend_lum(cb));
+    } else if (mode == 12) {
+        return blend_set_lum(blend_set_sat(cb, blend_sat(cs)), blend_lum(cb));
+    } else if (mode == 13) {
+        return blend_set_lum(cs, blend_lum(cb));
+    } else if (mode == 14) {
+        return blend_set_lum(cb, blend_lum(cs));
+    }
+    return cs;
+}
+
+/** Porter-Duff fractions (Fa, Fb) */
+vec2 blend_fractions(float alpha_s, float alpha_b, int op) {
+    if (op == 0) {
+        return vec2(0.0, 0.0);
+    } else if (op == 1) {
+        return vec2(1.0, 0.0);
+    } else if (op == 2) {
+        return vec2(0.0, 1.0);
+    } else if (op == 4) {
+        return vec2(1.0 - alpha_b, 1.0);
+    } else if (op == 5) {
+        return vec2(alpha_b, 0.0);
+    } else if (op == 6) {
+        return vec2(0.0, alpha_s);
+    } else if (op == 7) {
+        return vec2(1.0 - alpha_b, 0.0);
+    } else if (op == 8) {
+        return vec2(0.0, 1.0 - alpha_s);
+    } else if (op == 9) {
+        return vec2(alpha_b, 1.0 - alpha_s);
+    } else if (op == 10) {
+        return vec2(1.0 - alpha_b, alpha_s);
+    } else if (op == 11) {
+        return vec2(1.0 - alpha_b, 1.0 - alpha_s);
+    } else if (op == 12) {
+        return vec2(1.0, 1.0);
+    }
+    return vec2(1.0, 1.0 - alpha_s);
+}
+
+vec3 blend_straight(vec4 c) {
+    return c.a > 0.0001 ? c.rgb / c.a : vec3(0.0);
+}
+
+/** Blends premultiplied source with premultiplied backdrop and composites the result */
+vec4 blend_composite(vec4 backdrop, vec4 source, int mode, int op) {
+    vec3 cb = blend_straight(backdrop);
+    vec3 cs = blend_straight(source);
+    cs = (1.0 - backdrop.a) * cs + backdrop.a * blend_mode(cb, cs, mode);
+
+    vec2 f = blend_fractions(source.a, backdrop.a, op);
+    return vec4(f.x * source.a * cs + f.y * backdrop.rgb, f.x * source.a + f.y * backdrop.a);
+}
@@ -0,0 +1,104 @@
+/*
+ * Seeded coherent noise, mirrors src/geometry/noise.rs.
+ * `permutation` is a 256x1 texture made from Noise::permutation_bytes.
+ */
+
+float noise_hash(sampler2D permutation, float i) {
+    float index = mod(i, 256.0);
+    return floor(texture2D(permutation, vec2((index + 0.5) / 256.0, 0.5)).r * 255.0 + 0.5);
+}
+
+float noise_hash2(sampler2D permutation, vec2 i) {
+    return noise_hash(permutation, noise_hash(permutation, i.x) + i.y);
+}
+
+vec2 noise_fade(vec2 t) {
+    return t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+}
+
+/* Gradient of Noise::permutation_bytes, its green and blue hold x and y plus one */
+float noise_gradient2(sampler2D permutation, float hash, vec2 p) {
+    vec2 uv = vec2((mod(hash, 256.0) + 0.5) / 256.0, 0.5);
+    vec2 g = floor(texture2D(permutation, uv).gb * 255.0 + 0.5) - 1.0;
+    return dot(g, p);
+}
+
+float noise_perlin2(sampler2D permutation, vec2 point) {
+    vec2 i = floor(point);
+    vec2 f = point - i;
+    vec2 u = noise_fade(f);
+    return mix(
+        mix(
+            noise_gradient2(permutation, noise_hash2(permutation, i), f),
+            noise_gradient2(permutation, noise_hash2(permutation, i + vec2(1.0, 0.0)), f - vec2(1.0, 0.0)),
+            u.x
+        ),
+        mix(
+            noise_gradient2(permutation, noise_hash2(permutation, i + vec2(0.0, 1.0)), f - vec2(0.0, 1.0)),
+            noise_gradient2(permutation, noise_hash2(permutation, i + vec2(1.0, 1.0)), f - vec2(1.0, 1.0)),
+            u.x
+        ),
+        u.y
+    );
+}
+
+float noise_simplex_corner(sampler2D permutation, vec2 cell, vec2 p) {
+    float t = 0.5 - dot(p, p);
+    if (t < 0.0) {
+        return 0.0;
+    }
+    t *= t;
+    return t * t * noise_gradient2(permutation, noise_hash2(permutation, cell), p);
+}
+
+float noise_simplex2(sampler2D permutation, vec2 point) {
+    const float F2 = 0.36602542;
+    const float G2 = 0.21132487;
+
+    vec2 i = floor(point + (point.x + point.y) * F2);
+    vec2 p0 = point - (i - (i.x + i.y) * G2);
+    vec2 o = p0.x > p0.y ? vec2(1.0, 0.0) : vec2(0.0, 1.0);
+
+    return 70.0 * (
+        noise_simplex_corner(permutation, i, p0) +
+        noise_simplex_corner(permutation, i + o, p0 - o + G2) +
+        noise_simplex_corner(permutation, i + 1.0, p0 - 1.0 + 2.0 * G2)
+    );
+}
+
+float noise_worley2(sampler2D permutation, vec2 point) {
+    vec2 i = floor(point);
+    vec2 f = point - i;
+    float nearest = 10.0;
+    for (int dj = -1; dj <= 1; dj++) {
+        for (int di = -1; di <= 1; di++) {
+            vec2 d = vec2(float(di), float(dj));
+            float hash = noise_hash2(permutation, i + d);
+            vec2 feature = d + vec2(
+                noise_hash(permutation, hash),
+                noise_hash(permutation, hash + 1.0)
+            ) / 255.0;
+            vec2 delta = feature - f;
+            nearest = min(nearest, dot(delta, delta));
+        }
+    }
+    return sqrt(nearest);
+}
+
+/* fbm from src/geometry/noise.rs over noise_perlin2, octaves are limited to 8 */
+float noise_fbm2(sampler2D permutation, vec2 point, int octaves, float lacunarity, float gain) {
+    float sum = 0.0;
+    float total = 0.0;
+    float frequency = 1.0;
+    float amplitude = 1.0;
+    for (int octave = 0; octave < 8; octave++) {
+        if (octave >= octaves) {
+            break;
+        }
+        sum += amplitude * noise_perlin2(permutation, point * frequency);
+        total += amplitude;
+        frequency *= lacunarity;
+        amplitude *= gain;
+    }
+    return total > 0.0 ? sum / total : 0.0;
+}
@@ -0,0 +1,19 @@
+/*
+ * sRGB transfer function, mirrors linear_to_srgb in src/geometry/color.rs.
+ */
+
+float srgb_encode(float v) {
+    return v <= 0.0031308 ? v * 12.92 : 1.055 * pow(v, 1.0 / 2.4) - 0.055;
+}
+
+/* Premultiplied linear color to premultiplied sRGB, the format of canvas drawing buffer */
+vec4 srgb_from_linear(vec4 color) {
+    if (color.a <= 0.0001) {
+        return vec4(0.0, 0.0, 0.0, color.a);
+    }
+    vec3 straight = color.rgb / color.a;
+    return vec4(
+        vec3(srgb_encode(straight.r), srgb_encode(straight.g), srgb_encode(straight.b)) * color.a,
+        color.a
+    );
+}
@@ -0,0 +1,172 @@
+use crate::context::color_from_values;
+use crate::geometry::{self, Color, ColorMetric, Hsl, Hsv, Lab, OkLab, OkLch, Srgb, StraightColor};
+use wasm_bindgen::prelude::*;
+
+fn values(color: Color) -> Vec<f32> {
+    vec![color.r, color.g, color.b, color.a]
+}
+
+/// @param color is an array [r, g, b, a] of premultiplied linear color
+/// @returns an array [r, g, b, a] of straight linear color
+#[wasm_bindgen]
+pub fn color_to_straight(color: Vec<f32>) -> Vec<f32> {
+    let c = StraightColor::from(color_from_values(&color));
+    vec![c.r, c.g, c.b, c.a]
+}
+
+/// @param straight is an array [r, g, b, a] of straight linear color
+/// @returns an array [r, g, b, a] of premultiplied linear color
+#[wasm_bindgen]
+pub fn color_from_straight(straight: Vec<f32>) -> Vec<f32> {
+    assert_eq!(straight.len(), 4);
+    values(StraightColor::new(straight[0], straight[1], straight[2], straight[3]).into())
+}
+
+/// @param color is an array [r, g, b, a] of premultiplied linear color
+/// @returns an array [r, g, b, a] of straight sRGB color
+#[wasm_bindgen]
+pub fn color_to_srgb(color: Vec<f32>) -> Vec<f32> {
+    let c = Srgb::from(color_from_values(&color));
+    vec![c.r, c.g, c.b, c.a]
+}
+
+/// @param srgb is an array [r, g, b, a] of straight sRGB color
+/// @returns an array [r, g, b, a] of premultiplied linear color
+#[wasm_bindgen]
+pub fn color_from_srgb(srgb: Vec<f32>) -> Vec<f32> {
+    assert_eq!(srgb.len(), 4);
+    values(Srgb::new(srgb[0], srgb[1], srgb[2], srgb[3]).into())
+}
+
+/// @param color is an array [r, g, b, a] of premultiplied linear color
+/// @returns an array [r, g, b, a] of straight 8-bit sRGB color
+#[wasm_bindgen]
+pub fn color_to_srgb8(color: Vec<f32>) -> Vec<u8> {
+    color_from_values(&color).to_srgb8().to_vec()
+}
+
+/// @param srgb is an array [r, g, b, a] of straight 8-bit sRGB color
+/// @returns an array [r, g, b, a] of premultiplied linear color
+#[wasm_bindgen]
+pub fn color_from_srgb8(srgb: Vec<u8>) -> Vec<f32> {
+    assert_eq!(srgb.len(), 4);
+    values(Color::from_srgb8([srgb[0], srgb[1], srgb[2], srgb[3]]))
+}
+
+/// @param color is an array [r, g, b, a] of premultiplied linear color
+/// @returns an array [r, g, b, a] of straight 16-bit sRGB color
+#[wasm_bindgen]
+pub fn color_to_srgb16(color: Vec<f32>) -> Vec<u16> {
+    color_from_values(&color).to_srgb16().to_vec()
+}
+
+/// @param srgb is an array [r, g, b, a] of straight 16-bit sRGB color
+/// @returns an array [r, g, b, a] of premultiplied linear color
+#[wasm_bindgen]
+pub fn color_from_srgb16(srgb: Vec<u16>) -> Vec<f32> {
+    assert_eq!(srgb.len(), 4);
+    values(Color::from_srgb16([srgb[0], srgb[1], srgb[2], srgb[3]]))
+}
+
+/// @param color is an array [r, g, b, a] of premultiplied linear color
+/// @returns an array [h, s, v, a], hue is in degrees
+#[wasm_bindgen]
+pub fn color_to_hsv(color: Vec<f32>) -> Vec<f32> {
+    let c = Hsv::from(color_from_values(&color));
+    vec![c.h, c.s, c.v, c.a]
+}
+
+/// @param hsv is an array [h, s, v, a], hue is in degrees
+/// @returns an array [r, g, b, a] of premultiplied linear color
+#[wasm_bindgen]
+pub fn color_from_hsv(hsv: Vec<f32>) -> Vec<f32> {
+    assert_eq!(hsv.len(), 4);
+    values(Hsv::new(hsv[0], hsv[1], hsv[2], hsv[3]).into())
+}
+
+/// @param color is an array [r, g, b, a] of premultiplied linear color
+/// @returns an array [h, s, l, a], hue is in degrees
+#[wasm_bindgen]
+pub fn color_to_hsl(color: Vec<f32>) -> Vec<f32> {
+    let c = Hsl::from(color_from_values(&color));
+    vec![c.h, c.s, c.l, c.a]
+}
+
+/// @param hsl is an array [h, s, l, a], hue is in degrees
+/// @returns an array [r, g, b, a] of premultiplied linear color
+#[wasm_bindgen]
+pub fn color_from_hsl(hsl: Vec<f32>) -> Vec<f32> {
+    assert_eq!(hsl.len(), 4);
+    values(Hsl::new(hsl[0], hsl[1], hsl[2], hsl[3]).into())
+}
+
+/// @param color is an array [r, g, b, a] of premultiplied linear color
+/// @returns an array [l, a, b, alpha]
+#[wasm_bindgen]
+pub fn color_to_oklab(color: Vec<f32>) -> Vec<f32> {
+    let c = OkLab::from(color_from_values(&color));
+    vec![c.l, c.a, c.b, c.alpha]
+}
+
+/// @param lab is an array [l, a, b, alpha]
+/// @returns an array [r, g, b, a] of premultiplied linear color
+#[wasm_bindgen]
+pub fn color_from_oklab(lab: Vec<f32>) -> Vec<f32> {
+    assert_eq!(lab.len(), 4);
+    values(OkLab::new(lab[0], lab[1], lab[2], lab[3]).into())
+}
+
+/// @param color is an array [r, g, b, a] of premultiplied linear color
+/// @returns an array [l, c, h, alpha], hue is in degrees
+#[wasm_bindgen]
+pub fn color_to_oklch(color: Vec<f32>) -> Vec<f32> {
+    let c = OkLch::from(color_from_values(&color));
+    vec![c.l, c.c, c.h, c.alpha]
+}
+
+/// @param lch is an array [l, c, h, alpha], hue is in degrees
+/// @returns an array [r, g, b, a] of premultiplied linear color
+#[wasm_bindgen]
+pub fn color_from_oklch(lch: Vec<f32>) -> Vec<f32> {
+    assert_eq!(lch.len(), 4);
+    values(OkLch::new(lch[0], lch[1], lch[2], lch[3]).into())
+}
+
+/// @param color is an array [r, g, b, a] of premultiplied linear color
+/// @returns an array [l, a, b, alpha] of CIE L*a*b* color with D65 white point
+#[wasm_bindgen]
+pub fn color_to_lab(color: Vec<f32>) -> Vec<f32> {
+    let c = Lab::from(color_from_values(&color));
+    vec![c.l, c.a, c.b, c.alpha]
+}
+
+/// @param lab is an array [l, a, b, alpha] of CIE L*a*b* color with D65 white point
+/// @returns an array [r, g, b, a] of premultiplied linear color
+#[wasm_bindgen]
+pub fn color_from_lab(lab: Vec<f32>) -> Vec<f32> {
+    assert_eq!(lab.len(), 4);
+    values(Lab::new(lab[0], lab[1], lab[2], lab[3]).into())
+}
+
+/// Perceptual difference between two colors
+/// @param color1 is an array [r, g, b, a] of premultiplied linear color
+/// @param color2 is an array [r, g, b, a] of premultiplied linear color
+#[wasm_bindgen]
+pub fn color_distance(color1: Vec<f32>, color2: Vec<f32>, metric: ColorMetric) -> f32 {
+    color_from_values(&color1).distance_by(color_from_values(&color2), metric)
+}
+
+/// Mixes colors like paints with Kubelka-Munk model, so blue and yellow give green
+/// @param colors is an array [r, g, b, a, r, g, b, a, ...] of premultiplied linear colors
+/// @param weights is an array of paint amounts, one for every color
+/// @returns an array [r, g, b, a] of premultiplied linear color
+#[wasm_bindgen]
+pub fn mix_pigments(colors: Vec<f32>, weights: Vec<f32>) -> Vec<f32> {
+    assert_eq!(colors.len(), weights.len() * 4);
+    let paints: Vec<_> = colors
+        .chunks(4)
+        .map(color_from_values)
+        .zip(weights)
+        .collect();
+    values(geometry::mix_pigments(&paints))
+}
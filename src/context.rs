@@ -1,8 +1,10 @@
 use crate::frame::Frame;
-use crate::geometry::Vector;
+use crate::geometry::{Color, PremultipliedSrgb, Rect, Vector};
+use crate::json::Json;
 use crate::pool::{Pool, PoolEntry, PoolRequest};
-use crate::tool::ToolRef;
-use crate::tools::WavePencil;
+use crate::preset::Preset;
+use crate::tool::{tool_with_parameters, ToolRef};
+use crate::tools::{tool_by_name, WavePencil, TOOL_NAMES};
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlCanvasElement;
@@ -178,37 +180,60 @@ impl Context {
             thickness,
             amplitude,
             period,
+            ..Default::default()
         })
     }
 
-    /// @param bounds is an array [left, top, right, bottom]
-    /// @param color is an array [r, g, b, a]
-    pub fn clear(&self, bounds: Vec<i32>, color: Vec<f32>) {
-        assert_eq!(bounds.len(), 4);
-        assert_eq!(color.len(), 4);
+    /// Tool by name, see `tool_schemas`
+    /// @param parameters is a JSON object, missing parameters take default values
+    #[wasm_bindgen(catch)]
+    pub fn tool(&self, name: &str, parameters: &str) -> Result<ToolRef, JsValue> {
+        Ok(ToolRef::from_box(tool_with_parameters(
+            name,
+            &Json::parse(parameters)?,
+        )?))
+    }
+
+    /// Restores a tool saved by `ToolRef::to_preset`
+    #[wasm_bindgen(catch)]
+    pub fn tool_from_preset(&self, bytes: &[u8]) -> Result<ToolRef, JsValue> {
+        Ok(ToolRef::from_box(Preset::from_bytes(bytes)?.to_tool()?))
+    }
+
+    /// JSON object with parameter descriptions of every tool by tool name,
+    /// `{"wave_pencil": [{"name", "type", "min", "max", "default", "unit"}, ...], ...}`
+    pub fn tool_schemas(&self) -> String {
+        TOOL_NAMES
+            .iter()
+            .filter_map(|name| tool_by_name(name))
+            .fold(Json::object(), |json, tool| {
+                json.with(tool.name(), tool.schema_json())
+            })
+            .to_string()
+    }
+
+    pub(crate) fn screen(&self) -> Rect {
+        Rect::with_size(
+            0.0,
+            0.0,
+            self.canvas().width() as f32,
+            self.canvas().height() as f32,
+        )
+    }
+
+    /// Canvas drawing buffer keeps premultiplied sRGB values
+    pub(crate) fn clear_rect(&self, rect: &Rect, color: PremultipliedSrgb) {
+        let [x, y, width, height] = rect.to_xywh();
         self.gl().apply(
             Gl::settings()
-                .viewport(bounds[0], bounds[1], bounds[2], bounds[3])
-                .clear_color(color[0], color[1], color[2], color[3]),
+                .viewport(x, y, width, height)
+                .clear_color(color.r, color.g, color.b, color.a),
             || self.gl().clear_color_buffer(),
         )
     }
 
-    pub fn clear_full_screen(&self, color: Vec<f32>) {
-        self.clear(
-            vec![
-                0,
-                0,
-                self.canvas().width() as i32,
-                self.canvas().height() as i32,
-            ],
-            color,
-        );
-    }
-
-    /// @param bounds is an array [left, top, right, bottom]
-    #[wasm_bindgen(catch)]
-    pub fn draw_frame_1(&self, frame: &Frame, bounds: Vec<i32>) -> Result<(), JsValue> {
+    /// Frames keep premultiplied linear values, they are encoded to sRGB of the canvas
+    pub(crate) fn draw_frame_1_rect(&self, frame: &Frame, rect: &Rect) -> Result<(), GlError> {
         let program = self.program(
             load_glsl!("draw-frame/1.f.glsl"),
             load_glsl!("draw-frame/1.v.glsl"),
@@ -227,12 +252,7 @@ impl Context {
                     PrimitiveType::TriangleFan,
                     &Draw1Uniforms {
                         resolution: (w as f32, h as f32),
-                        area: (
-                            bounds[0] as f32,
-                            bounds[1] as f32,
-                            bounds[2] as f32,
-                            bounds[3] as f32,
-                        ),
+                        area: (rect.left, rect.top, rect.width(), rect.height()),
                         source: frame.texture(),
                     },
                     &self.gl().items_buffer(
@@ -247,25 +267,35 @@ impl Context {
                 );
                 Ok(())
             },
-        )?;
+        )
+    }
+
+    /// @param bounds is an array [x, y, width, height]
+    /// @param color is an array [r, g, b, a] of premultiplied linear color
+    pub fn clear(&self, bounds: Vec<i32>, color: Vec<f32>) {
+        self.clear_rect(&rect_from_bounds(&bounds), color_from_values(&color).into())
+    }
 
+    /// @param color is an array [r, g, b, a] of premultiplied linear color
+    pub fn clear_full_screen(&self, color: Vec<f32>) {
+        self.clear_rect(&self.screen(), color_from_values(&color).into());
+    }
+
+    /// @param bounds is an array [x, y, width, height]
+    #[wasm_bindgen(catch)]
+    pub fn draw_frame_1(&self, frame: &Frame, bounds: Vec<i32>) -> Result<(), JsValue> {
+        self.draw_frame_1_rect(frame, &rect_from_bounds(&bounds))?;
         Ok(())
     }
 
     #[wasm_bindgen(catch)]
     pub fn draw_frame_1_full_screen(&self, frame: &Frame) -> Result<(), JsValue> {
-        self.draw_frame_1(
-            frame,
-            vec![
-                0,
-                0,
-                self.canvas().width() as i32,
-                self.canvas().height() as i32,
-            ],
-        )
+        self.draw_frame_1_rect(frame, &self.screen())?;
+        Ok(())
     }
 
     /// Mix background and foreground in linear colorspace
+    /// @param bounds is an array [x, y, width, height]
     #[wasm_bindgen(catch)]
     pub fn draw_frame_2(
         &self,
@@ -282,19 +312,27 @@ impl Context {
         background: &Frame,
         foreground: &Frame,
     ) -> Result<(), JsValue> {
-        self.draw_frame_2(
-            background,
-            foreground,
-            vec![
-                0,
-                0,
-                self.canvas().width() as i32,
-                self.canvas().height() as i32,
-            ],
-        )
+        self.draw_frame_2(background, foreground, self.screen().to_xywh().to_vec())
     }
 }
 
+/// Converts JS bounds array [x, y, width, height]
+fn rect_from_bounds(bounds: &[i32]) -> Rect {
+    assert_eq!(bounds.len(), 4);
+    Rect::with_size(
+        bounds[0] as f32,
+        bounds[1] as f32,
+        bounds[2] as f32,
+        bounds[3] as f32,
+    )
+}
+
+/// Converts JS color array [r, g, b, a] of premultiplied linear color
+pub(crate) fn color_from_values(color: &[f32]) -> Color {
+    assert_eq!(color.len(), 4);
+    Color::new(color[0], color[1], color[2], color[3])
+}
+
 #[derive(Clone, Debug, Uniforms)]
 struct Draw1Uniforms {
     resolution: (f32, f32),
@@ -0,0 +1,323 @@
+use crate::geometry::{fbm, Noise, PressureCurve};
+use crate::json::{Json, JsonError};
+use std::f32::consts::PI;
+use wasm_bindgen::prelude::*;
+
+/// Distance between input events, in screen pixels, giving velocity 1
+const MAX_VELOCITY: f32 = 50.0;
+/// Layers of the `Noise` sensor, each next one has half the wavelength and amplitude
+const NOISE_OCTAVES: u32 = 3;
+
+/// Input values driving tool parameters, all of them are in range [0, 1]
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sensor {
+    Pressure = 0,
+    /// Distance between input events, input is expected to come with constant rate
+    Velocity = 1,
+    Tilt = 2,
+    /// Angle of movement, full turn is 1
+    Direction = 3,
+    /// Distance along the stroke, repeats every `length` pixels
+    Distance = 4,
+    /// Goes from 1 to 0 along the first `length` pixels of the stroke
+    Fade = 5,
+    /// Random value for every input event
+    Random = 6,
+    /// Smooth random value along the stroke, `length` pixels is the longest wavelength
+    Noise = 7,
+}
+
+const SENSORS: [(Sensor, &str); 8] = [
+    (Sensor::Pressure, "pressure"),
+    (Sensor::Velocity, "velocity"),
+    (Sensor::Tilt, "tilt"),
+    (Sensor::Direction, "direction"),
+    (Sensor::Distance, "distance"),
+    (Sensor::Fade, "fade"),
+    (Sensor::Random, "random"),
+    (Sensor::Noise, "noise"),
+];
+
+impl Sensor {
+    pub fn name(self) -> &'static str {
+        SENSORS.iter().find(|(s, _)| *s == self).unwrap().1
+    }
+
+    pub fn from_name(name: &str) -> Option<Sensor> {
+        SENSORS.iter().find(|(_, n)| *n == name).map(|(s, _)| *s)
+    }
+}
+
+/// Raw sensor readings of one input event
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SensorValues {
+    pub pressure: f32,
+    /// Distance between input events in screen pixels
+    pub velocity: f32,
+    pub tilt: f32,
+    /// Angle of movement in radians
+    pub direction: f32,
+    /// Distance from the stroke start in pixels
+    pub offset: f32,
+    pub random: f32,
+}
+
+impl SensorValues {
+    fn value(&self, sensor: Sensor, length: f32, noise: &Noise) -> f32 {
+        let length = length.max(1.0);
+        match sensor {
+            Sensor::Pressure => self.pressure,
+            Sensor::Velocity => self.velocity / MAX_VELOCITY,
+            Sensor::Tilt => self.tilt,
+            Sensor::Direction => (self.direction / (2.0 * PI)).rem_euclid(1.0),
+            Sensor::Distance => (self.offset / length).rem_euclid(1.0),
+            Sensor::Fade => 1.0 - self.offset / length,
+            Sensor::Random => self.random,
+            Sensor::Noise => {
+                let x = self.offset / length;
+                0.5 + 0.5 * fbm(NOISE_OCTAVES, 2.0, 0.5, |f| noise.perlin1(x * f))
+            }
+        }
+        .clamp(0.0, 1.0)
+    }
+}
+
+/// Sensor mapped through a curve to a factor in range [min, max] of a tool parameter
+#[derive(Clone, Debug, PartialEq)]
+pub struct DynamicsMapping {
+    pub sensor: Sensor,
+    pub parameter: String,
+    pub min: f32,
+    pub max: f32,
+    /// Length in pixels for `Distance`, `Fade` and `Noise` sensors
+    pub length: f32,
+    pub curve: PressureCurve,
+}
+
+impl DynamicsMapping {
+    /// @param noise is the generator of the `Noise` sensor, seeded per stroke
+    pub fn factor(&self, values: &SensorValues, noise: &Noise) -> f32 {
+        let t = self
+            .curve
+            .apply(values.value(self.sensor, self.length, noise));
+        self.min + (self.max - self.min) * t
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::object()
+            .with("sensor", self.sensor.name())
+            .with("parameter", self.parameter.as_str())
+            .with("min", self.min)
+            .with("max", self.max)
+            .with("length", self.length)
+            .with("curve", self.curve.to_json())
+    }
+
+    pub fn from_json(json: &Json) -> Result<DynamicsMapping, JsonError> {
+        let sensor = json.str_field("sensor")?;
+        Ok(DynamicsMapping {
+            sensor: Sensor::from_name(sensor)
+                .ok_or_else(|| JsonError::new(format!("Unknown sensor \"{}\"", sensor)))?,
+            parameter: json.str_field("parameter")?.to_string(),
+            min: json.f32_field("min")?,
+            max: json.f32_field("max")?,
+            length: json.f32_field("length")?,
+            curve: PressureCurve::from_json(json.field("curve")?)?,
+        })
+    }
+}
+
+/// Per sample modulation of tool parameters.
+///
+/// Every parameter value is multiplied by factors of all mappings targeting it,
+/// parameters without mappings are used as is.
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Dynamics {
+    mappings: Vec<DynamicsMapping>,
+}
+
+#[wasm_bindgen]
+impl Dynamics {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Dynamics {
+        Default::default()
+    }
+
+    /// @param length is used by distance, fade and noise sensors, in pixels
+    pub fn add(
+        &mut self,
+        sensor: Sensor,
+        parameter: String,
+        min: f32,
+        max: f32,
+        length: f32,
+        curve: &PressureCurve,
+    ) {
+        self.mappings.push(DynamicsMapping {
+            sensor,
+            parameter,
+            min,
+            max,
+            length,
+            curve: curve.clone(),
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.mappings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    pub fn to_json_string(&self) -> String {
+        self.to_json().to_string()
+    }
+
+    #[wasm_bindgen(catch)]
+    pub fn from_json_string(text: &str) -> Result<Dynamics, JsValue> {
+        Ok(Dynamics::from_json(&Json::parse(text)?)?)
+    }
+}
+
+impl Dynamics {
+    pub fn mappings(&self) -> &[DynamicsMapping] {
+        &self.mappings
+    }
+
+    /// Names of modulated parameters in the order of factors returned by `factors`
+    pub fn parameters(&self) -> Vec<&str> {
+        let mut result: Vec<&str> = Vec::new();
+        for m in self.mappings.iter() {
+            if !result.contains(&m.parameter.as_str()) {
+                result.push(&m.parameter);
+            }
+        }
+        result
+    }
+
+    /// Index of the parameter factor, `None` if the parameter isn't modulated
+    pub fn index(&self, parameter: &str) -> Option<usize> {
+        self.parameters().iter().position(|p| *p == parameter)
+    }
+
+    pub fn factors(&self, values: &SensorValues, noise: &Noise) -> Vec<f32> {
+        let parameters = self.parameters();
+        let mut result = vec![1.0; parameters.len()];
+        for m in self.mappings.iter() {
+            let index = parameters.iter().position(|p| *p == m.parameter).unwrap();
+            result[index] *= m.factor(values, noise);
+        }
+        result
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::object().with(
+            "mappings",
+            Json::Array(self.mappings.iter().map(|m| m.to_json()).collect()),
+        )
+    }
+
+    pub fn from_json(json: &Json) -> Result<Dynamics, JsonError> {
+        Ok(Dynamics {
+            mappings: json
+                .array_field("mappings")?
+                .iter()
+                .map(DynamicsMapping::from_json)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dynamics, Sensor, SensorValues};
+    use crate::geometry::{Noise, PressureCurve};
+    use crate::json::Json;
+
+    fn dynamics() -> Dynamics {
+        let mut dynamics = Dynamics::new();
+        dynamics.add(
+            Sensor::Pressure,
+            "size".into(),
+            0.2,
+            1.0,
+            0.0,
+            &PressureCurve::linear(),
+        );
+        dynamics.add(
+            Sensor::Fade,
+            "opacity".into(),
+            0.0,
+            1.0,
+            100.0,
+            &PressureCurve::bezier(0.0, 0.5, 1.0, 1.0),
+        );
+        dynamics.add(
+            Sensor::Random,
+            "size".into(),
+            0.5,
+            1.0,
+            0.0,
+            &PressureCurve::points(vec![0.0, 0.0, 1.0, 1.0]),
+        );
+        dynamics
+    }
+
+    #[test]
+    fn factors() {
+        let dynamics = dynamics();
+        assert_eq!(dynamics.parameters(), vec!["size", "opacity"]);
+        assert_eq!(dynamics.index("opacity"), Some(1));
+        assert_eq!(dynamics.index("spacing"), None);
+
+        let values = SensorValues {
+            pressure: 0.5,
+            offset: 25.0,
+            random: 1.0,
+            ..Default::default()
+        };
+        let factors = dynamics.factors(&values, &Noise::new(0));
+        assert!((factors[0] - 0.6).abs() < 1e-6);
+        // Bézier at 0.75
+        assert!((factors[1] - 0.9140625).abs() < 1e-6);
+    }
+
+    #[test]
+    fn noise() {
+        let mut dynamics = Dynamics::new();
+        let linear = PressureCurve::linear();
+        dynamics.add(Sensor::Noise, "size".into(), 0.0, 1.0, 50.0, &linear);
+        let noise = Noise::new(4);
+        let factor = |offset: f32| {
+            let values = SensorValues {
+                offset,
+                ..Default::default()
+            };
+            dynamics.factors(&values, &noise)[0]
+        };
+        let samples: Vec<f32> = (0..1000).map(|i| factor(i as f32 * 0.5)).collect();
+        assert!(samples.iter().all(|v| (0.0..=1.0).contains(v)));
+        // Smooth along the stroke, unlike the random sensor
+        assert!(samples.windows(2).all(|w| (w[0] - w[1]).abs() < 0.05));
+        assert!(samples.iter().any(|v| (v - 0.5).abs() > 0.1));
+        assert_eq!(factor(0.0), 0.5);
+    }
+
+    #[test]
+    fn serialization() {
+        let dynamics = dynamics();
+        let text = dynamics.to_json_string();
+        assert_eq!(
+            Dynamics::from_json(&Json::parse(&text).unwrap()),
+            Ok(dynamics)
+        );
+        assert!(
+            Dynamics::from_json(&Json::parse(r#"{"mappings":[{"sensor":"smell"}]}"#).unwrap())
+                .is_err()
+        );
+    }
+}
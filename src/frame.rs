@@ -1,18 +1,26 @@
-use crate::context::Context;
-use crate::geometry::Color;
+use crate::context::{color_from_values, Context};
+use crate::geometry::{BlendMode, CompositeOperator, Gradient, Rect, Vector};
+use crate::path::{DashPattern, DynamicsState};
 use crate::pool::PoolEntry;
+use crate::stroke::Stroke;
+use crate::symmetry::Symmetry;
 use crate::tool::ToolRef;
 use crate::touch_list::TouchList;
 use std::mem::replace;
 use std::ops::Deref;
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlImageElement;
-use webgl_rc::{Gl, GlError, Settings, Texture, TextureFilter, TextureFormat, TextureType};
+use webgl_rc::{
+    load_glsl, Attributes, BufferUsage, Gl, GlError, PrimitiveType, Settings, Texture,
+    TextureFilter, TextureFormat, TextureType, Uniforms,
+};
 
 #[wasm_bindgen]
 pub struct Frame {
     context: Context,
     texture: PoolEntry<Texture>,
+    /// Area modified since the last `reset_dirty`
+    dirty: Rect,
 }
 
 #[wasm_bindgen]
@@ -21,11 +29,17 @@ impl Frame {
         Ok(Self {
             texture: context.texture(size, TextureFormat::Rgba, TextureFilter::Linear)?,
             context,
+            dirty: Rect::empty(),
         })
     }
 
     pub(crate) fn from_texture(context: Context, texture: PoolEntry<Texture>) -> Frame {
-        Frame { context, texture }
+        let dirty = Rect::with_size(0.0, 0.0, texture.width() as f32, texture.height() as f32);
+        Frame {
+            context,
+            texture,
+            dirty,
+        }
     }
 
     pub(crate) fn from_image(context: Context, image: HtmlImageElement) -> Result<Frame, GlError> {
@@ -36,7 +50,7 @@ impl Frame {
         )?;
         texture.write_image(&image)?;
 
-        Ok(Frame { context, texture })
+        Ok(Frame::from_texture(context, texture))
     }
 
     pub(crate) fn gl(&self) -> Gl {
@@ -55,10 +69,57 @@ impl Frame {
         replace(&mut self.texture, texture)
     }
 
+    /// Copy of the frame content
+    pub(crate) fn copy_texture(&self) -> Result<PoolEntry<Texture>, GlError> {
+        let context = self.context();
+        let gl = self.gl();
+        let size = self.size();
+        let target = context.texture(size, TextureFormat::Rgba, TextureFilter::Linear)?;
+        let program = context.program(
+            load_glsl!("composite/copy.f.glsl"),
+            load_glsl!("composite/composite.v.glsl"),
+        )?;
+
+        gl.apply(
+            Gl::settings()
+                .blend(false)
+                .depth_test(false)
+                .frame_buffer(gl.frame_buffer_with_color(target.deref().clone())?)
+                .viewport(0, 0, size.0 as i32, size.1 as i32),
+            || -> Result<(), GlError> {
+                program.draw_arrays(
+                    PrimitiveType::TriangleFan,
+                    &CopyUniforms {
+                        source: self.texture(),
+                    },
+                    &gl.items_buffer(
+                        &[
+                            CompositeAttributes::new(0.0, 0.0),
+                            CompositeAttributes::new(1.0, 0.0),
+                            CompositeAttributes::new(1.0, 1.0),
+                            CompositeAttributes::new(0.0, 1.0),
+                        ],
+                        BufferUsage::Stream,
+                    )?,
+                );
+                Ok(())
+            },
+        )?;
+        Ok(target)
+    }
+
     pub(crate) fn size(&self) -> (u32, u32) {
         (self.width(), self.height())
     }
 
+    pub(crate) fn bounds(&self) -> Rect {
+        Rect::with_size(0.0, 0.0, self.width() as f32, self.height() as f32)
+    }
+
+    pub(crate) fn mark_dirty(&mut self, rect: &Rect) {
+        self.dirty = self.dirty.union(&rect.snap()).intersection(&self.bounds());
+    }
+
     pub fn width(&self) -> u32 {
         self.texture.width()
     }
@@ -66,7 +127,7 @@ impl Frame {
         self.texture.height()
     }
 
-    /// @param color array containing [r, g, b, a]
+    /// @param color array containing [r, g, b, a] of premultiplied linear color
     /// @param phases three elements array [p1, p2, p3]
     #[wasm_bindgen(catch)]
     pub fn draw(
@@ -77,22 +138,268 @@ impl Frame {
         phases: Vec<f32>,
         seed: i32,
     ) -> Result<(), JsValue> {
-        assert_eq!(color.len(), 4);
         assert_eq!(phases.len(), 3);
 
-        tool.draw(
+        let modified = tool.draw(
             self,
             path,
-            Color::new(color[0], color[1], color[2], color[3]),
+            color_from_values(&color),
             [phases[0], phases[1], phases[2]],
             seed,
         )?;
+        self.mark_dirty(&modified);
         Ok(())
     }
 
+    /// Draws every copy of the path produced by `symmetry` with the same color and seed,
+    /// copies are drawn in one pass and join where they cross
+    /// @param color array containing [r, g, b, a] of premultiplied linear color
+    /// @param phases three elements array [p1, p2, p3]
+    #[wasm_bindgen(catch)]
+    pub fn draw_symmetric(
+        &mut self,
+        tool: &ToolRef,
+        path: &TouchList,
+        symmetry: &Symmetry,
+        color: Vec<f32>,
+        phases: Vec<f32>,
+        seed: i32,
+    ) -> Result<(), JsValue> {
+        assert_eq!(phases.len(), 3);
+
+        let modified = tool.draw_copies(
+            self,
+            &symmetry.apply(path),
+            color_from_values(&color),
+            [phases[0], phases[1], phases[2]],
+            seed,
+        )?;
+        self.mark_dirty(&modified);
+        Ok(())
+    }
+
+    /// Draws every dash of the path as a separate stroke, dashes get caps of the tool
+    /// @param color array containing [r, g, b, a] of premultiplied linear color
+    /// @param phases three elements array [p1, p2, p3]
+    #[wasm_bindgen(catch)]
+    pub fn draw_dashed(
+        &mut self,
+        tool: &ToolRef,
+        path: &TouchList,
+        pattern: &DashPattern,
+        color: Vec<f32>,
+        phases: Vec<f32>,
+        seed: i32,
+    ) -> Result<(), JsValue> {
+        assert_eq!(phases.len(), 3);
+
+        // One state along the source path, so sensors continue across the gaps
+        let mut state = DynamicsState::new(seed);
+        for dash in pattern.dashes(path) {
+            state.advance(path, dash.index);
+            let touches = dash.path(path);
+            let modified = tool.draw_part(
+                self,
+                &touches,
+                0..touches.len().saturating_sub(1),
+                color_from_values(&color),
+                [phases[0], phases[1], phases[2]],
+                &state.rebased(dash.offset),
+            )?;
+            self.mark_dirty(&modified);
+        }
+        Ok(())
+    }
+
+    /// Starts a stroke drawn incrementally with `Stroke.extend` and `Stroke.end`,
+    /// the stroke keeps the tool settings of this moment
+    /// @param color array containing [r, g, b, a] of premultiplied linear color
+    /// @param phases three elements array [p1, p2, p3]
+    pub fn begin_stroke(
+        &self,
+        tool: &ToolRef,
+        color: Vec<f32>,
+        phases: Vec<f32>,
+        seed: i32,
+    ) -> Stroke {
+        assert_eq!(phases.len(), 3);
+        Stroke::new(
+            tool.as_ref(),
+            color_from_values(&color),
+            [phases[0], phases[1], phases[2]],
+            seed,
+        )
+    }
+
+    /// Fills the frame with premultiplied linear color
     #[wasm_bindgen(catch)]
-    pub fn clear(&self, r: f32, g: f32, b: f32, alpha: f32) -> Result<(), JsValue> {
+    pub fn clear(&mut self, r: f32, g: f32, b: f32, alpha: f32) -> Result<(), JsValue> {
         self.texture.clear(r, g, b, alpha)?;
+        self.mark_dirty(&self.bounds());
+        Ok(())
+    }
+
+    /// Blends `source` frame over this one, the same way as `Color::composite`
+    #[wasm_bindgen(catch)]
+    pub fn composite(
+        &mut self,
+        source: &Frame,
+        mode: BlendMode,
+        operator: CompositeOperator,
+    ) -> Result<(), JsValue> {
+        let context = self.context();
+        let gl = self.gl();
+        let size = self.size();
+        let target = context.texture(size, TextureFormat::Rgba, TextureFilter::Linear)?;
+        let program = context.program(
+            load_glsl!("composite/composite.f.glsl"),
+            load_glsl!("composite/composite.v.glsl"),
+        )?;
+
+        gl.apply(
+            Gl::settings()
+                .blend(false)
+                .depth_test(false)
+                .frame_buffer(gl.frame_buffer_with_color(target.deref().clone())?)
+                .viewport(0, 0, size.0 as i32, size.1 as i32),
+            || -> Result<(), GlError> {
+                program.draw_arrays(
+                    PrimitiveType::TriangleFan,
+                    &CompositeUniforms {
+                        backdrop: self.texture(),
+                        source: source.texture(),
+                        mode: mode as i32 as f32,
+                        operator: operator as i32 as f32,
+                    },
+                    &gl.items_buffer(
+                        &[
+                            CompositeAttributes::new(0.0, 0.0),
+                            CompositeAttributes::new(1.0, 0.0),
+                            CompositeAttributes::new(1.0, 1.0),
+                            CompositeAttributes::new(0.0, 1.0),
+                        ],
+                        BufferUsage::Stream,
+                    )?,
+                );
+                Ok(())
+            },
+        )?;
+
+        self.replace_texture(target);
+        self.mark_dirty(&self.bounds());
+        Ok(())
+    }
+
+    /// Fills the frame with gradient, replacing its content
+    #[wasm_bindgen(catch)]
+    pub fn fill_gradient(&mut self, gradient: &Gradient) -> Result<(), JsValue> {
+        self.draw_gradient(gradient)?;
         Ok(())
     }
+
+    pub(crate) fn draw_gradient(&mut self, gradient: &Gradient) -> Result<(), GlError> {
+        let context = self.context();
+        let gl = self.gl();
+        let size = self.size();
+
+        let stops = context.texture(
+            (GRADIENT_SAMPLES, 1),
+            TextureFormat::Rgba,
+            TextureFilter::Linear,
+        )?;
+        let bytes = (0..GRADIENT_SAMPLES)
+            .flat_map(|i| {
+                let c = gradient.color_at(i as f32 / (GRADIENT_SAMPLES - 1) as f32);
+                [c.r, c.g, c.b, c.a].map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
+            })
+            .collect();
+        stops.write_bytes(&bytes)?;
+
+        let program = context.program(
+            load_glsl!("gradient/gradient.f.glsl"),
+            load_glsl!("gradient/gradient.v.glsl"),
+        )?;
+
+        gl.apply(
+            Gl::settings()
+                .blend(false)
+                .depth_test(false)
+                .frame_buffer(gl.frame_buffer_with_color(self.texture())?)
+                .viewport(0, 0, size.0 as i32, size.1 as i32),
+            || -> Result<(), GlError> {
+                program.draw_arrays(
+                    PrimitiveType::TriangleFan,
+                    &GradientUniforms {
+                        stops: stops.deref().clone(),
+                        resolution: Vector::new(size.0 as f32, size.1 as f32),
+                        start: gradient.start(),
+                        axis: gradient.axis(),
+                        shape: gradient.shape() as i32 as f32,
+                        spread: gradient.spread() as i32 as f32,
+                    },
+                    &gl.items_buffer(
+                        &[
+                            CompositeAttributes::new(0.0, 0.0),
+                            CompositeAttributes::new(1.0, 0.0),
+                            CompositeAttributes::new(1.0, 1.0),
+                            CompositeAttributes::new(0.0, 1.0),
+                        ],
+                        BufferUsage::Stream,
+                    )?,
+                );
+                Ok(())
+            },
+        )?;
+
+        self.mark_dirty(&self.bounds());
+        Ok(())
+    }
+
+    /// Area modified since the last reset, an array [x, y, width, height]
+    pub fn dirty_bounds(&self) -> Vec<i32> {
+        self.dirty.to_xywh().to_vec()
+    }
+
+    pub fn reset_dirty(&mut self) {
+        self.dirty = Rect::empty();
+    }
+}
+
+#[derive(Clone, Debug, Uniforms)]
+struct CompositeUniforms {
+    backdrop: Texture,
+    source: Texture,
+    mode: f32,
+    operator: f32,
+}
+
+#[derive(Clone, Debug, Uniforms)]
+struct CopyUniforms {
+    source: Texture,
+}
+
+/// Width of the texture with gradient colors
+const GRADIENT_SAMPLES: u32 = 256;
+
+#[derive(Clone, Debug, Uniforms)]
+struct GradientUniforms {
+    stops: Texture,
+    resolution: Vector,
+    start: Vector,
+    axis: Vector,
+    shape: f32,
+    spread: f32,
+}
+
+#[derive(Clone, Copy, Debug, Attributes)]
+struct CompositeAttributes {
+    position: Vector,
+}
+
+impl CompositeAttributes {
+    fn new(x: f32, y: f32) -> Self {
+        Self {
+            position: Vector::new(x, y),
+        }
+    }
 }
@@ -0,0 +1,297 @@
+use super::color::Color;
+use super::straight_color::StraightColor;
+use wasm_bindgen::prelude::*;
+
+/// Blend modes from the W3C Compositing and Blending spec.
+/// Values must match `glsl/lib/blend.glsl`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendMode {
+    Normal = 0,
+    Multiply = 1,
+    Screen = 2,
+    Overlay = 3,
+    Darken = 4,
+    Lighten = 5,
+    ColorDodge = 6,
+    ColorBurn = 7,
+    HardLight = 8,
+    SoftLight = 9,
+    Difference = 10,
+    Hue = 11,
+    Saturation = 12,
+    Color = 13,
+    Luminosity = 14,
+}
+
+/// Porter-Duff compositing operators.
+/// Values must match `glsl/lib/blend.glsl`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompositeOperator {
+    Clear = 0,
+    Source = 1,
+    Destination = 2,
+    SourceOver = 3,
+    DestinationOver = 4,
+    SourceIn = 5,
+    DestinationIn = 6,
+    SourceOut = 7,
+    DestinationOut = 8,
+    SourceAtop = 9,
+    DestinationAtop = 10,
+    Xor = 11,
+    Lighter = 12,
+}
+
+type Rgb = [f32; 3];
+
+fn hard_light(cb: f32, cs: f32) -> f32 {
+    if cs <= 0.5 {
+        cb * 2.0 * cs
+    } else {
+        let s = 2.0 * cs - 1.0;
+        cb + s - cb * s
+    }
+}
+
+fn soft_light(cb: f32, cs: f32) -> f32 {
+    if cs <= 0.5 {
+        cb - (1.0 - 2.0 * cs) * cb * (1.0 - cb)
+    } else {
+        let d = if cb <= 0.25 {
+            ((16.0 * cb - 12.0) * cb + 4.0) * cb
+        } else {
+            cb.sqrt()
+        };
+        cb + (2.0 * cs - 1.0) * (d - cb)
+    }
+}
+
+fn color_dodge(cb: f32, cs: f32) -> f32 {
+    if cb <= 0.0 {
+        0.0
+    } else if cs >= 1.0 {
+        1.0
+    } else {
+        (cb / (1.0 - cs)).min(1.0)
+    }
+}
+
+fn color_burn(cb: f32, cs: f32) -> f32 {
+    if cb >= 1.0 {
+        1.0
+    } else if cs <= 0.0 {
+        0.0
+    } else {
+        1.0 - ((1.0 - cb) / cs).min(1.0)
+    }
+}
+
+fn separable(cb: Rgb, cs: Rgb, f: impl Fn(f32, f32) -> f32) -> Rgb {
+    [f(cb[0], cs[0]), f(cb[1], cs[1]), f(cb[2], cs[2])]
+}
+
+fn lum(c: Rgb) -> f32 {
+    0.3 * c[0] + 0.59 * c[1] + 0.11 * c[2]
+}
+
+fn clip_color(c: Rgb) -> Rgb {
+    let l = lum(c);
+    let n = c[0].min(c[1]).min(c[2]);
+    let x = c[0].max(c[1]).max(c[2]);
+    let mut c = c;
+    if n < 0.0 {
+        c = c.map(|v| l + (v - l) * l / (l - n));
+    }
+    if x > 1.0 {
+        c = c.map(|v| l + (v - l) * (1.0 - l) / (x - l));
+    }
+    c
+}
+
+fn set_lum(c: Rgb, l: f32) -> Rgb {
+    let d = l - lum(c);
+    clip_color(c.map(|v| v + d))
+}
+
+fn sat(c: Rgb) -> f32 {
+    c[0].max(c[1]).max(c[2]) - c[0].min(c[1]).min(c[2])
+}
+
+fn set_sat(c: Rgb, s: f32) -> Rgb {
+    let n = c[0].min(c[1]).min(c[2]);
+    let x = c[0].max(c[1]).max(c[2]);
+    if x > n {
+        c.map(|v| (v - n) * s / (x - n))
+    } else {
+        [0.0; 3]
+    }
+}
+
+impl BlendMode {
+    /// Blending function B(Cb, Cs) on straight color components
+    fn apply(self, cb: Rgb, cs: Rgb) -> Rgb {
+        match self {
+            BlendMode::Normal => cs,
+            BlendMode::Multiply => separable(cb, cs, |b, s| b * s),
+            BlendMode::Screen => separable(cb, cs, |b, s| b + s - b * s),
+            BlendMode::Overlay => separable(cb, cs, |b, s| hard_light(s, b)),
+            BlendMode::Darken => separable(cb, cs, f32::min),
+            BlendMode::Lighten => separable(cb, cs, f32::max),
+            BlendMode::ColorDodge => separable(cb, cs, color_dodge),
+            BlendMode::ColorBurn => separable(cb, cs, color_burn),
+            BlendMode::HardLight => separable(cb, cs, hard_light),
+            BlendMode::SoftLight => separable(cb, cs, soft_light),
+            BlendMode::Difference => separable(cb, cs, |b, s| (b - s).abs()),
+            BlendMode::Hue => set_lum(set_sat(cs, sat(cb)), lum(cb)),
+            BlendMode::Saturation => set_lum(set_sat(cb, sat(cs)), lum(cb)),
+            BlendMode::Color => set_lum(cs, lum(cb)),
+            BlendMode::Luminosity => set_lum(cb, lum(cs)),
+        }
+    }
+}
+
+impl CompositeOperator {
+    /// Porter-Duff fractions (Fa, Fb) for source and backdrop alpha
+    fn fractions(self, alpha_s: f32, alpha_b: f32) -> (f32, f32) {
+        match self {
+            CompositeOperator::Clear => (0.0, 0.0),
+            CompositeOperator::Source => (1.0, 0.0),
+            CompositeOperator::Destination => (0.0, 1.0),
+            CompositeOperator::SourceOver => (1.0, 1.0 - alpha_s),
+            CompositeOperator::DestinationOver => (1.0 - alpha_b, 1.0),
+            CompositeOperator::SourceIn => (alpha_b, 0.0),
+            CompositeOperator::DestinationIn => (0.0, alpha_s),
+            CompositeOperator::SourceOut => (1.0 - alpha_b, 0.0),
+            CompositeOperator::DestinationOut => (0.0, 1.0 - alpha_s),
+            CompositeOperator::SourceAtop => (alpha_b, 1.0 - alpha_s),
+            CompositeOperator::DestinationAtop => (1.0 - alpha_b, alpha_s),
+            CompositeOperator::Xor => (1.0 - alpha_b, 1.0 - alpha_s),
+            CompositeOperator::Lighter => (1.0, 1.0),
+        }
+    }
+}
+
+impl Color {
+    /// Composites `source` over `self` as backdrop with blend mode
+    pub fn blend(self, source: Color, mode: BlendMode) -> Color {
+        self.composite(source, mode, CompositeOperator::SourceOver)
+    }
+
+    /// Blends `source` with `self` as backdrop, and then composites the result
+    pub fn composite(self, source: Color, mode: BlendMode, operator: CompositeOperator) -> Color {
+        let b = StraightColor::from(self);
+        let s = StraightColor::from(source);
+        let cb = [b.r, b.g, b.b];
+        let cs = [s.r, s.g, s.b];
+
+        let mixed = mode.apply(cb, cs);
+        // Where backdrop is transparent the source color is kept unchanged
+        let cs = [0, 1, 2].map(|i| (1.0 - b.a) * cs[i] + b.a * mixed[i]);
+
+        let (fa, fb) = operator.fractions(s.a, b.a);
+        Color::new(
+            fa * s.a * cs[0] + fb * self.r,
+            fa * s.a * cs[1] + fb * self.g,
+            fa * s.a * cs[2] + fb * self.b,
+            fa * s.a + fb * self.a,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlendMode, CompositeOperator};
+    use crate::geometry::Color;
+
+    fn assert_close(a: Color, b: Color) {
+        for (x, y) in [(a.r, b.r), (a.g, b.g), (a.b, b.b), (a.a, b.a)] {
+            assert!((x - y).abs() < 0.0001, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn normal_source_over() {
+        let backdrop = Color::new(0.0, 0.0, 1.0, 1.0);
+        let source = Color::new(0.5, 0.0, 0.0, 0.5);
+        assert_close(
+            backdrop.blend(source, BlendMode::Normal),
+            Color::new(0.5, 0.0, 0.5, 1.0),
+        );
+    }
+
+    #[test]
+    fn separable_modes() {
+        let backdrop = Color::new(0.5, 0.25, 1.0, 1.0);
+        let source = Color::new(0.5, 1.0, 0.0, 1.0);
+        assert_close(
+            backdrop.blend(source, BlendMode::Multiply),
+            Color::new(0.25, 0.25, 0.0, 1.0),
+        );
+        assert_close(
+            backdrop.blend(source, BlendMode::Screen),
+            Color::new(0.75, 1.0, 1.0, 1.0),
+        );
+        assert_close(
+            backdrop.blend(source, BlendMode::Difference),
+            Color::new(0.0, 0.75, 1.0, 1.0),
+        );
+        assert_close(
+            backdrop.blend(source, BlendMode::Darken),
+            Color::new(0.5, 0.25, 0.0, 1.0),
+        );
+    }
+
+    #[test]
+    fn luminosity_keeps_backdrop_hue() {
+        let backdrop = Color::new(1.0, 0.0, 0.0, 1.0);
+        let source = Color::new(0.3, 0.3, 0.3, 1.0);
+        let result = backdrop.blend(source, BlendMode::Luminosity);
+        assert!((0.3 * result.r + 0.59 * result.g + 0.11 * result.b - 0.3).abs() < 0.0001);
+        assert_eq!(result.g, result.b);
+    }
+
+    #[test]
+    fn transparent_backdrop_keeps_source() {
+        let source = Color::new(0.2, 0.4, 0.1, 0.5);
+        for mode in [
+            BlendMode::Multiply,
+            BlendMode::ColorBurn,
+            BlendMode::Hue,
+            BlendMode::SoftLight,
+        ] {
+            assert_close(Color::new(0.0, 0.0, 0.0, 0.0).blend(source, mode), source);
+        }
+    }
+
+    #[test]
+    fn porter_duff() {
+        let backdrop = Color::new(0.0, 0.5, 0.0, 0.5);
+        let source = Color::new(0.5, 0.0, 0.0, 0.5);
+        let composite = |operator| backdrop.composite(source, BlendMode::Normal, operator);
+
+        assert_close(
+            composite(CompositeOperator::Clear),
+            Color::new(0.0, 0.0, 0.0, 0.0),
+        );
+        assert_close(composite(CompositeOperator::Source), source);
+        assert_close(composite(CompositeOperator::Destination), backdrop);
+        assert_close(
+            composite(CompositeOperator::SourceIn),
+            Color::new(0.25, 0.0, 0.0, 0.25),
+        );
+        assert_close(
+            composite(CompositeOperator::DestinationOut),
+            Color::new(0.0, 0.25, 0.0, 0.25),
+        );
+        assert_close(
+            composite(CompositeOperator::Xor),
+            Color::new(0.25, 0.25, 0.0, 0.5),
+        );
+        assert_close(
+            composite(CompositeOperator::Lighter),
+            Color::new(0.5, 0.5, 0.0, 1.0),
+        );
+    }
+}
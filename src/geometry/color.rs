@@ -3,8 +3,12 @@ use std::ops::{Add, Div, Mul};
 use webgl_rc::{DataType, IntoUniform, TypeMark, UniformValue, Writable};
 
 use super::mix::Mix;
+use super::srgb::Srgb;
+use super::straight_color::StraightColor;
 
 /// Premultiplied linear RGBA color
+///
+/// Use `StraightColor`, `Srgb` or `PremultipliedSrgb` conversions for other representations.
 #[derive(Clone, Copy, PartialEq, Default, Debug)]
 pub struct Color {
     pub r: f32,
@@ -13,34 +17,50 @@ pub struct Color {
     pub a: f32,
 }
 
-fn linear(v: f32) -> f32 {
-    v.powf(2.2)
+/// sRGB electro-optical transfer function, decodes sRGB component to linear
+pub fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// sRGB opto-electronic transfer function, inverse of `srgb_to_linear`
+pub fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
 }
 
 impl Color {
+    /// Components are expected to be premultiplied already
     pub fn new(r: f32, g: f32, b: f32, a: f32) -> Color {
-        Color {
-            r: r * a,
-            g: g * a,
-            b: b * a,
-            a,
-        }
+        Color { r, g, b, a }
     }
-    pub fn srgb(r: f32, g: f32, b: f32, a: f32) -> Color {
-        Color {
-            r: linear(r) * a,
-            g: linear(g) * a,
-            b: linear(b) * a,
-            a,
-        }
+    /// Straight 8-bit sRGB components [r, g, b, a]
+    pub fn to_srgb8(self) -> [u8; 4] {
+        Srgb::from(self).to_u8()
+    }
+    pub fn from_srgb8(values: [u8; 4]) -> Color {
+        Srgb::from_u8(values).into()
+    }
+    /// Straight 16-bit sRGB components [r, g, b, a]
+    pub fn to_srgb16(self) -> [u16; 4] {
+        Srgb::from(self).to_u16()
+    }
+    pub fn from_srgb16(values: [u16; 4]) -> Color {
+        Srgb::from_u16(values).into()
     }
+    /// The same color with alpha set to 1
     pub fn opaque(&self) -> Color {
-        if self.a > 0.0001 {
-            let k = 1.0 / self.a;
-            Color::new(self.r * k, self.g * k, self.b * k, 1.0)
-        } else {
-            Color::new(0.0, 0.0, 0.0, 1.0)
+        StraightColor {
+            a: 1.0,
+            ..StraightColor::from(*self)
         }
+        .into()
     }
 }
 
@@ -165,12 +185,14 @@ impl IntoUniform for Color {
     }
 }
 
+/// Premultiplied components [r, g, b, a]
 impl From<[f32; 4]> for Color {
     fn from(values: [f32; 4]) -> Self {
         Color::new(values[0], values[1], values[2], values[3])
     }
 }
 
+/// Premultiplied components (r, g, b, a)
 impl From<(f32, f32, f32, f32)> for Color {
     fn from(values: (f32, f32, f32, f32)) -> Self {
         Color::new(values.0, values.1, values.2, values.3)
@@ -0,0 +1,71 @@
+use super::color::Color;
+use super::distance::Distance;
+use super::lab::Lab;
+use super::oklab::OkLab;
+use wasm_bindgen::prelude::*;
+
+/// Perceptual color difference metrics
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorMetric {
+    /// Euclidean distance in OKLab, just noticeable difference is about 0.02
+    OkLab = 0,
+    /// CIEDE2000, just noticeable difference is about 1
+    Ciede2000 = 1,
+}
+
+/// Euclidean distance, alpha difference is added as another dimension
+impl Distance for OkLab {
+    type Output = f32;
+    fn distance(self, other: Self) -> f32 {
+        let d = [
+            other.l - self.l,
+            other.a - self.a,
+            other.b - self.b,
+            other.alpha - self.alpha,
+        ];
+        d.iter().map(|v| v * v).sum::<f32>().sqrt()
+    }
+}
+
+impl Color {
+    pub fn distance_by(self, other: Color, metric: ColorMetric) -> f32 {
+        match metric {
+            ColorMetric::OkLab => OkLab::from(self).distance(OkLab::from(other)),
+            ColorMetric::Ciede2000 => Lab::from(self).distance(Lab::from(other)),
+        }
+    }
+}
+
+/// OKLab distance, use `distance_by` to choose another metric
+impl Distance for Color {
+    type Output = f32;
+    fn distance(self, other: Self) -> f32 {
+        self.distance_by(other, ColorMetric::OkLab)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ColorMetric;
+    use crate::geometry::{Color, Distance};
+
+    #[test]
+    fn perceptual_distance() {
+        let black = Color::new(0.0, 0.0, 0.0, 1.0);
+        let white = Color::new(1.0, 1.0, 1.0, 1.0);
+        assert!((black.distance(white) - 1.0).abs() < 0.0001);
+        assert!((black.distance_by(white, ColorMetric::Ciede2000) - 100.0).abs() < 0.01);
+
+        // Equal steps in linear RGB are much more visible in dark colors
+        let dark = Color::new(0.01, 0.01, 0.01, 1.0).distance(Color::new(0.02, 0.02, 0.02, 1.0));
+        let light = Color::new(0.8, 0.8, 0.8, 1.0).distance(Color::new(0.81, 0.81, 0.81, 1.0));
+        assert!(dark > 5.0 * light);
+
+        let c = Color::new(0.1, 0.2, 0.3, 1.0);
+        for metric in [ColorMetric::OkLab, ColorMetric::Ciede2000] {
+            assert_eq!(c.distance_by(c, metric), 0.0);
+            assert!(c.distance_by(c * 0.5, metric) > 0.0);
+        }
+    }
+}
@@ -6,16 +6,16 @@ pub trait Curve<T: Mix + Clone>: Clone {
 }
 
 #[derive(Clone, PartialEq, Debug)]
-pub struct Curve0<T: Mix + Clone>(T);
+pub struct Curve0<T: Mix + Clone>(pub T);
 
 #[derive(Clone, PartialEq, Debug)]
-pub struct Curve1<T: Mix + Clone>(T, T);
+pub struct Curve1<T: Mix + Clone>(pub T, pub T);
 
 #[derive(Clone, PartialEq, Debug)]
-pub struct Curve2<T: Mix + Clone>(T, T, T);
+pub struct Curve2<T: Mix + Clone>(pub T, pub T, pub T);
 
 #[derive(Clone, PartialEq, Debug)]
-pub struct Curve3<T: Mix + Clone>(T, T, T, T);
+pub struct Curve3<T: Mix + Clone>(pub T, pub T, pub T, pub T);
 
 impl<T: Mix + Clone + Copy> Copy for Curve0<T> {}
 impl<T: Mix + Clone + Copy> Copy for Curve1<T> {}
@@ -60,7 +60,7 @@ impl<T: Mix + Clone> Curve<T> for Curve3<T> {
         if t == zero() {
             self.0.clone()
         } else if t == one() {
-            self.1.clone()
+            self.3.clone()
         } else {
             let v1 = self.0.clone().mix(self.1.clone(), t);
             let v2 = self.1.clone().mix(self.2.clone(), t);
@@ -0,0 +1,12 @@
+use std::f32::consts::PI;
+
+/// Smooth step from 0 at `t <= 0` to 1 at `t >= 1`
+pub fn easing(t: f32) -> f32 {
+    if t >= 1.0 {
+        1.0
+    } else if t <= 0.0 {
+        0.0
+    } else {
+        ((PI * t).cos() - 1.0) * -0.5
+    }
+}
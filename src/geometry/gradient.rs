@@ -0,0 +1,272 @@
+use super::color::Color;
+use super::hsv::Hsv;
+use super::mix::Mix;
+use super::oklab::OkLab;
+use super::vector::Vector;
+use std::f32::consts::PI;
+use wasm_bindgen::prelude::*;
+
+/// How the gradient parameter is computed from a point.
+/// Values must match `glsl/gradient/gradient.f.glsl`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GradientShape {
+    /// Parameter is a projection to the line from `start` to `end`
+    Linear = 0,
+    /// Circles around `start`, `end` is on the circle with parameter 1
+    Radial = 1,
+    /// Angle around `start`, starting from direction to `end`
+    Conic = 2,
+    /// Squares around `start`, `end` is a corner with parameter 1
+    Diamond = 3,
+}
+
+/// Color space used to interpolate between stops
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GradientInterpolation {
+    /// Premultiplied linear RGB
+    Linear = 0,
+    /// Premultiplied OKLab
+    OkLab = 1,
+    /// HSV with hue going the shortest way around the color wheel
+    Hsv = 2,
+}
+
+/// What happens with parameter outside of [0, 1].
+/// Values must match `glsl/gradient/gradient.f.glsl`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GradientSpread {
+    Pad = 0,
+    Repeat = 1,
+    Reflect = 2,
+}
+
+impl GradientSpread {
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            GradientSpread::Pad => t.clamp(0.0, 1.0),
+            GradientSpread::Repeat => t.rem_euclid(1.0),
+            GradientSpread::Reflect => 1.0 - (t.rem_euclid(2.0) - 1.0).abs(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GradientStop {
+    pub offset: f32,
+    pub color: Color,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, PartialEq, Debug)]
+pub struct Gradient {
+    shape: GradientShape,
+    interpolation: GradientInterpolation,
+    spread: GradientSpread,
+    start: Vector,
+    end: Vector,
+    /// Sorted by offset
+    stops: Vec<GradientStop>,
+}
+
+#[wasm_bindgen]
+impl Gradient {
+    /// Gradient from point (x1, y1) to point (x2, y2), without stops,
+    /// with linear interpolation and pad spread
+    #[wasm_bindgen(constructor)]
+    pub fn new(shape: GradientShape, x1: f32, y1: f32, x2: f32, y2: f32) -> Gradient {
+        Gradient {
+            shape,
+            interpolation: GradientInterpolation::Linear,
+            spread: GradientSpread::Pad,
+            start: Vector::new(x1, y1),
+            end: Vector::new(x2, y2),
+            stops: Vec::new(),
+        }
+    }
+
+    pub fn set_interpolation(&mut self, interpolation: GradientInterpolation) {
+        self.interpolation = interpolation;
+    }
+
+    pub fn set_spread(&mut self, spread: GradientSpread) {
+        self.spread = spread;
+    }
+
+    /// @param color is an array [r, g, b, a] of premultiplied linear color
+    pub fn add_stop(&mut self, offset: f32, color: Vec<f32>) {
+        assert_eq!(color.len(), 4);
+        self.push_stop(offset, Color::new(color[0], color[1], color[2], color[3]));
+    }
+
+    /// @returns an array [r, g, b, a] of premultiplied linear color at point (x, y)
+    pub fn evaluate(&self, x: f32, y: f32) -> Vec<f32> {
+        let c = self.color_at_point(Vector::new(x, y));
+        vec![c.r, c.g, c.b, c.a]
+    }
+}
+
+impl Gradient {
+    pub fn shape(&self) -> GradientShape {
+        self.shape
+    }
+    pub fn spread(&self) -> GradientSpread {
+        self.spread
+    }
+    pub fn start(&self) -> Vector {
+        self.start
+    }
+
+    /// Stops with the same offset are kept in the order of adding, so they make a hard edge
+    pub fn push_stop(&mut self, offset: f32, color: Color) {
+        let index = self.stops.partition_point(|s| s.offset <= offset);
+        self.stops.insert(index, GradientStop { offset, color });
+    }
+
+    /// `end - start` scaled so projection of `point - start` to it gives the parameter,
+    /// zero if `start` and `end` are the same point
+    pub fn axis(&self) -> Vector {
+        let d = self.end - self.start;
+        let length = d.x * d.x + d.y * d.y;
+        if length > 0.0 {
+            d / length
+        } else {
+            Vector::new(0.0, 0.0)
+        }
+    }
+
+    /// Gradient parameter at point before applying spread
+    pub fn parameter(&self, point: Vector) -> f32 {
+        let axis = self.axis();
+        let p = point - self.start;
+        let u = axis.x * p.x + axis.y * p.y;
+        let v = axis.x * p.y - axis.y * p.x;
+        match self.shape {
+            GradientShape::Linear => u,
+            GradientShape::Radial => u.hypot(v),
+            GradientShape::Conic => (v.atan2(u) / (2.0 * PI)).rem_euclid(1.0),
+            GradientShape::Diamond => u.abs() + v.abs(),
+        }
+    }
+
+    /// Color at parameter `t` in range [0, 1], transparent if there are no stops
+    pub fn color_at(&self, t: f32) -> Color {
+        let index = self.stops.partition_point(|s| s.offset <= t);
+        match (index.checked_sub(1), self.stops.get(index)) {
+            (None, None) => Color::new(0.0, 0.0, 0.0, 0.0),
+            (Some(i), None) => self.stops[i].color,
+            (None, Some(next)) => next.color,
+            (Some(i), Some(next)) => {
+                let previous = &self.stops[i];
+                let f = (t - previous.offset) / (next.offset - previous.offset);
+                self.interpolate(previous.color, next.color, f)
+            }
+        }
+    }
+
+    pub fn color_at_point(&self, point: Vector) -> Color {
+        self.color_at(self.spread.apply(self.parameter(point)))
+    }
+
+    fn interpolate(&self, c1: Color, c2: Color, t: f32) -> Color {
+        match self.interpolation {
+            GradientInterpolation::Linear => c1.mix(c2, t),
+            GradientInterpolation::OkLab => {
+                let (l1, l2) = (OkLab::from(c1), OkLab::from(c2));
+                let premultiplied = |c: OkLab| [c.l * c.alpha, c.a * c.alpha, c.b * c.alpha];
+                let (p1, p2) = (premultiplied(l1), premultiplied(l2));
+                let alpha = l1.alpha.mix(l2.alpha, t);
+                if alpha <= 0.0 {
+                    return Color::new(0.0, 0.0, 0.0, 0.0);
+                }
+                let [l, a, b] = [0, 1, 2].map(|i| p1[i].mix(p2[i], t) / alpha);
+                OkLab::new(l, a, b, alpha).into()
+            }
+            GradientInterpolation::Hsv => {
+                let (h1, h2) = (Hsv::from(c1), Hsv::from(c2));
+                // Hue of gray colors is meaningless, take it from the other color
+                let (hue1, hue2) = match (h1.s > 0.0, h2.s > 0.0) {
+                    (false, true) => (h2.h, h2.h),
+                    (true, false) => (h1.h, h1.h),
+                    _ => (h1.h, h2.h),
+                };
+                let delta = (hue2 - hue1 + 180.0).rem_euclid(360.0) - 180.0;
+                Hsv::new(
+                    (hue1 + delta * t).rem_euclid(360.0),
+                    h1.s.mix(h2.s, t),
+                    h1.v.mix(h2.v, t),
+                    h1.a.mix(h2.a, t),
+                )
+                .into()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gradient, GradientInterpolation, GradientShape, GradientSpread};
+    use crate::geometry::{Color, Hsv, Vector};
+
+    fn assert_close(a: Color, b: Color) {
+        for (x, y) in [(a.r, b.r), (a.g, b.g), (a.b, b.b), (a.a, b.a)] {
+            assert!((x - y).abs() < 0.0001, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn shapes() {
+        let p = |shape| {
+            let g = Gradient::new(shape, 1.0, 1.0, 3.0, 1.0);
+            [
+                g.parameter(Vector::new(2.0, 1.0)),
+                g.parameter(Vector::new(1.0, 3.0)),
+                g.parameter(Vector::new(2.0, 2.0)),
+            ]
+        };
+        let check = |a: [f32; 3], b: [f32; 3]| {
+            for (x, y) in a.iter().zip(b.iter()) {
+                assert!((x - y).abs() < 0.0001, "{:?} != {:?}", a, b);
+            }
+        };
+        check(p(GradientShape::Linear), [0.5, 0.0, 0.5]);
+        check(p(GradientShape::Radial), [0.5, 1.0, 0.5f32.hypot(0.5)]);
+        check(p(GradientShape::Conic), [0.0, 0.25, 0.125]);
+        check(p(GradientShape::Diamond), [0.5, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn spread() {
+        assert_eq!(GradientSpread::Pad.apply(1.25), 1.0);
+        assert_eq!(GradientSpread::Pad.apply(-0.5), 0.0);
+        assert_eq!(GradientSpread::Repeat.apply(1.25), 0.25);
+        assert_eq!(GradientSpread::Repeat.apply(-0.25), 0.75);
+        assert_eq!(GradientSpread::Reflect.apply(1.25), 0.75);
+        assert_eq!(GradientSpread::Reflect.apply(-0.25), 0.25);
+    }
+
+    #[test]
+    fn stops() {
+        let red = Color::new(1.0, 0.0, 0.0, 1.0);
+        let blue = Color::new(0.0, 0.0, 1.0, 1.0);
+        let mut g = Gradient::new(GradientShape::Linear, 0.0, 0.0, 1.0, 0.0);
+        g.push_stop(0.75, blue);
+        g.push_stop(0.25, red);
+
+        assert_close(g.color_at(0.0), red);
+        assert_close(g.color_at(0.5), Color::new(0.5, 0.0, 0.5, 1.0));
+        assert_close(g.color_at(1.0), blue);
+
+        assert_eq!(g.evaluate(2.0, 5.0), vec![0.0, 0.0, 1.0, 1.0]);
+
+        g.set_interpolation(GradientInterpolation::Hsv);
+        // Shortest way from red to blue goes through magenta
+        assert!((Hsv::from(g.color_at(0.5)).h - 300.0).abs() < 0.01);
+
+        g.set_interpolation(GradientInterpolation::OkLab);
+        assert_close(g.color_at(0.25), red);
+        assert_close(g.color_at(0.75), blue);
+    }
+}
@@ -0,0 +1,54 @@
+use super::color::Color;
+use super::hsv::{from_hue, hue};
+use super::srgb::Srgb;
+
+/// Hue in degrees [0, 360), saturation and lightness in range [0, 1], straight alpha.
+/// Computed from sRGB encoded components.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Hsl {
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+    pub a: f32,
+}
+
+impl Hsl {
+    pub fn new(h: f32, s: f32, l: f32, a: f32) -> Hsl {
+        Hsl { h, s, l, a }
+    }
+}
+
+impl From<Srgb> for Hsl {
+    fn from(c: Srgb) -> Self {
+        let (h, delta) = hue(&c);
+        let max = c.r.max(c.g).max(c.b);
+        let min = c.r.min(c.g).min(c.b);
+        let l = (max + min) * 0.5;
+        let d = 1.0 - (2.0 * l - 1.0).abs();
+        Hsl {
+            h,
+            s: if d > 0.0 { delta / d } else { 0.0 },
+            l,
+            a: c.a,
+        }
+    }
+}
+
+impl From<Hsl> for Srgb {
+    fn from(c: Hsl) -> Self {
+        let chroma = (1.0 - (2.0 * c.l - 1.0).abs()) * c.s;
+        from_hue(c.h, chroma, c.l - chroma * 0.5, c.a)
+    }
+}
+
+impl From<Color> for Hsl {
+    fn from(color: Color) -> Self {
+        Srgb::from(color).into()
+    }
+}
+
+impl From<Hsl> for Color {
+    fn from(c: Hsl) -> Self {
+        Srgb::from(c).into()
+    }
+}
@@ -0,0 +1,122 @@
+use super::color::Color;
+use super::srgb::Srgb;
+
+/// Hue in degrees [0, 360), saturation and value in range [0, 1], straight alpha.
+/// Computed from sRGB encoded components, the same way color pickers do.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Hsv {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+    pub a: f32,
+}
+
+impl Hsv {
+    pub fn new(h: f32, s: f32, v: f32, a: f32) -> Hsv {
+        Hsv { h, s, v, a }
+    }
+}
+
+/// Hue in degrees, and `max - min` of sRGB components
+pub(crate) fn hue(c: &Srgb) -> (f32, f32) {
+    let max = c.r.max(c.g).max(c.b);
+    let min = c.r.min(c.g).min(c.b);
+    let delta = max - min;
+    let h = if delta <= 0.0 {
+        0.0
+    } else if max == c.r {
+        60.0 * ((c.g - c.b) / delta)
+    } else if max == c.g {
+        60.0 * ((c.b - c.r) / delta + 2.0)
+    } else {
+        60.0 * ((c.r - c.g) / delta + 4.0)
+    };
+    (h.rem_euclid(360.0), delta)
+}
+
+/// Maps hue and chroma to sRGB components, `m` is added to every component
+pub(crate) fn from_hue(h: f32, chroma: f32, m: f32, a: f32) -> Srgb {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    Srgb::new(r + m, g + m, b + m, a)
+}
+
+impl From<Srgb> for Hsv {
+    fn from(c: Srgb) -> Self {
+        let (h, delta) = hue(&c);
+        let v = c.r.max(c.g).max(c.b);
+        Hsv {
+            h,
+            s: if v > 0.0 { delta / v } else { 0.0 },
+            v,
+            a: c.a,
+        }
+    }
+}
+
+impl From<Hsv> for Srgb {
+    fn from(c: Hsv) -> Self {
+        let chroma = c.v * c.s;
+        from_hue(c.h, chroma, c.v - chroma, c.a)
+    }
+}
+
+impl From<Color> for Hsv {
+    fn from(color: Color) -> Self {
+        Srgb::from(color).into()
+    }
+}
+
+impl From<Hsv> for Color {
+    fn from(c: Hsv) -> Self {
+        Srgb::from(c).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hsv;
+    use crate::geometry::Srgb;
+
+    fn assert_close(a: Srgb, b: Srgb) {
+        for (x, y) in [(a.r, b.r), (a.g, b.g), (a.b, b.b), (a.a, b.a)] {
+            assert!((x - y).abs() < 0.0001, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn primaries() {
+        assert_eq!(
+            Hsv::from(Srgb::new(1.0, 0.0, 0.0, 1.0)),
+            Hsv::new(0.0, 1.0, 1.0, 1.0)
+        );
+        assert_eq!(
+            Hsv::from(Srgb::new(0.0, 0.5, 0.0, 1.0)),
+            Hsv::new(120.0, 1.0, 0.5, 1.0)
+        );
+        assert_eq!(
+            Hsv::from(Srgb::new(0.0, 0.0, 1.0, 0.5)),
+            Hsv::new(240.0, 1.0, 1.0, 0.5)
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        for c in [
+            Srgb::new(0.2, 0.4, 0.6, 1.0),
+            Srgb::new(0.9, 0.1, 0.3, 0.5),
+            Srgb::new(0.5, 0.5, 0.5, 1.0),
+            Srgb::new(0.7, 0.6, 0.1, 0.0),
+        ] {
+            assert_close(Srgb::from(Hsv::from(c)), c);
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use super::color::Color;
+use super::distance::Distance;
+use super::straight_color::StraightColor;
+
+/// CIE L*a*b* color with D65 white point and straight alpha, `l` is in range [0, 100]
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Lab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+const WHITE: [f32; 3] = [0.95047, 1.0, 1.08883];
+const EPSILON: f32 = 216.0 / 24389.0;
+const KAPPA: f32 = 24389.0 / 27.0;
+
+impl Lab {
+    pub fn new(l: f32, a: f32, b: f32, alpha: f32) -> Lab {
+        Lab { l, a, b, alpha }
+    }
+}
+
+fn lab_f(t: f32) -> f32 {
+    if t > EPSILON {
+        t.cbrt()
+    } else {
+        (KAPPA * t + 16.0) / 116.0
+    }
+}
+
+fn lab_f_inverse(f: f32) -> f32 {
+    let t = f * f * f;
+    if t > EPSILON {
+        t
+    } else {
+        (116.0 * f - 16.0) / KAPPA
+    }
+}
+
+impl From<Color> for Lab {
+    fn from(color: Color) -> Self {
+        let c = StraightColor::from(color);
+        let x = 0.4124564 * c.r + 0.3575761 * c.g + 0.1804375 * c.b;
+        let y = 0.2126729 * c.r + 0.7151522 * c.g + 0.072175 * c.b;
+        let z = 0.0193339 * c.r + 0.119192 * c.g + 0.9503041 * c.b;
+
+        let fx = lab_f(x / WHITE[0]);
+        let fy = lab_f(y / WHITE[1]);
+        let fz = lab_f(z / WHITE[2]);
+
+        Lab {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+            alpha: color.a,
+        }
+    }
+}
+
+impl From<Lab> for Color {
+    fn from(c: Lab) -> Self {
+        let fy = (c.l + 16.0) / 116.0;
+        let x = WHITE[0] * lab_f_inverse(fy + c.a / 500.0);
+        let y = WHITE[1] * lab_f_inverse(fy);
+        let z = WHITE[2] * lab_f_inverse(fy - c.b / 200.0);
+
+        StraightColor::new(
+            3.2404542 * x - 1.5371385 * y - 0.4985314 * z,
+            -0.969266 * x + 1.8760108 * y + 0.041556 * z,
+            0.0556434 * x - 0.2040259 * y + 1.0572252 * z,
+            c.alpha,
+        )
+        .into()
+    }
+}
+
+/// Hue angle in degrees [0, 360)
+fn hue(a: f32, b: f32) -> f32 {
+    if a == 0.0 && b == 0.0 {
+        0.0
+    } else {
+        b.atan2(a).to_degrees().rem_euclid(360.0)
+    }
+}
+
+impl Lab {
+    /// CIEDE2000 color difference of opaque colors, alpha is ignored.
+    /// See Sharma, Wu and Dalal, "The CIEDE2000 Color-Difference Formula".
+    pub fn ciede2000(self, other: Lab) -> f32 {
+        let pow7 = |v: f32| v.powi(7);
+        let k = pow7(25.0);
+
+        let c1 = self.a.hypot(self.b);
+        let c2 = other.a.hypot(other.b);
+        let c_mean = (c1 + c2) / 2.0;
+        let g = 0.5 * (1.0 - (pow7(c_mean) / (pow7(c_mean) + k)).sqrt());
+
+        let a1 = self.a * (1.0 + g);
+        let a2 = other.a * (1.0 + g);
+        let c1 = a1.hypot(self.b);
+        let c2 = a2.hypot(other.b);
+        let h1 = hue(a1, self.b);
+        let h2 = hue(a2, other.b);
+
+        let dl = other.l - self.l;
+        let dc = c2 - c1;
+        let dh = if c1 * c2 == 0.0 {
+            0.0
+        } else if (h2 - h1).abs() <= 180.0 {
+            h2 - h1
+        } else if h2 > h1 {
+            h2 - h1 - 360.0
+        } else {
+            h2 - h1 + 360.0
+        };
+        let dh = 2.0 * (c1 * c2).sqrt() * (dh / 2.0).to_radians().sin();
+
+        let l_mean = (self.l + other.l) / 2.0;
+        let c_mean = (c1 + c2) / 2.0;
+        let h_mean = if c1 * c2 == 0.0 {
+            h1 + h2
+        } else if (h1 - h2).abs() <= 180.0 {
+            (h1 + h2) / 2.0
+        } else if h1 + h2 < 360.0 {
+            (h1 + h2 + 360.0) / 2.0
+        } else {
+            (h1 + h2 - 360.0) / 2.0
+        };
+
+        let cos = |degrees: f32| degrees.to_radians().cos();
+        let t = 1.0 - 0.17 * cos(h_mean - 30.0)
+            + 0.24 * cos(2.0 * h_mean)
+            + 0.32 * cos(3.0 * h_mean + 6.0)
+            - 0.2 * cos(4.0 * h_mean - 63.0);
+        let theta = 30.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp();
+        let rc = 2.0 * (pow7(c_mean) / (pow7(c_mean) + k)).sqrt();
+        let l50 = (l_mean - 50.0).powi(2);
+        let sl = 1.0 + 0.015 * l50 / (20.0 + l50).sqrt();
+        let sc = 1.0 + 0.045 * c_mean;
+        let sh = 1.0 + 0.015 * c_mean * t;
+        let rt = -(2.0 * theta).to_radians().sin() * rc;
+
+        let (dl, dc, dh) = (dl / sl, dc / sc, dh / sh);
+        (dl * dl + dc * dc + dh * dh + rt * dc * dh).max(0.0).sqrt()
+    }
+}
+
+/// CIEDE2000 difference, alpha difference is added as another dimension scaled to [0, 100]
+impl Distance for Lab {
+    type Output = f32;
+    fn distance(self, other: Self) -> f32 {
+        let alpha = 100.0 * (other.alpha - self.alpha);
+        self.ciede2000(other).hypot(alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lab;
+    use crate::geometry::Color;
+
+    #[test]
+    fn ciede2000() {
+        // Test data from Sharma, Wu and Dalal
+        let pairs = [
+            ((50.0, 2.6772, -79.7751), (50.0, 0.0, -82.7485), 2.0425),
+            ((50.0, -1.3802, -84.2814), (50.0, 0.0, -82.7485), 1.0),
+            ((50.0, 2.5, 0.0), (73.0, 25.0, -18.0), 27.1492),
+            ((50.0, 2.5, 0.0), (50.0, 0.0, -2.5), 4.3065),
+            (
+                (60.2574, -34.0099, 36.2677),
+                (60.4626, -34.1751, 39.4387),
+                1.2644,
+            ),
+            (
+                (22.7233, 20.0904, -46.694),
+                (23.0331, 14.973, -42.5619),
+                2.0373,
+            ),
+            ((2.0776, 0.0795, -1.135), (0.9033, -0.0636, -0.5514), 0.9082),
+        ];
+        for ((l1, a1, b1), (l2, a2, b2), expected) in pairs {
+            let c1 = Lab::new(l1, a1, b1, 1.0);
+            let c2 = Lab::new(l2, a2, b2, 1.0);
+            assert!((c1.ciede2000(c2) - expected).abs() < 0.001);
+            assert!((c2.ciede2000(c1) - expected).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let white = Lab::from(Color::new(1.0, 1.0, 1.0, 1.0));
+        assert!((white.l - 100.0).abs() < 0.01 && white.a.abs() < 0.01 && white.b.abs() < 0.01);
+
+        for c in [
+            Color::new(0.2, 0.4, 0.6, 1.0),
+            Color::new(0.45, 0.05, 0.15, 0.5),
+            Color::new(0.001, 0.002, 0.0, 1.0),
+        ] {
+            let r = Color::from(Lab::from(c));
+            for (x, y) in [(r.r, c.r), (r.g, c.g), (r.b, c.b), (r.a, c.a)] {
+                assert!((x - y).abs() < 0.0001, "{:?} != {:?}", r, c);
+            }
+        }
+    }
+}
@@ -1,15 +1,45 @@
+mod blend;
 mod color;
+mod color_distance;
 mod curve;
 mod distance;
+mod easing;
+mod gradient;
+mod hsl;
+mod hsv;
+mod lab;
 mod mix;
+mod noise;
 mod normal;
+mod oklab;
+mod pigment;
+mod pressure_curve;
+mod rect;
+mod srgb;
+mod straight_color;
 mod touch;
+mod transform;
 mod vector;
 
+pub use blend::*;
 pub use color::*;
+pub use color_distance::*;
 pub use curve::*;
 pub use distance::*;
+pub use easing::*;
+pub use gradient::*;
+pub use hsl::*;
+pub use hsv::*;
+pub use lab::*;
 pub use mix::*;
+pub use noise::*;
 pub use normal::*;
+pub use oklab::*;
+pub use pigment::*;
+pub use pressure_curve::*;
+pub use rect::*;
+pub use srgb::*;
+pub use straight_color::*;
 pub use touch::*;
+pub use transform::*;
 pub use vector::*;
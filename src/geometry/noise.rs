@@ -0,0 +1,357 @@
+use crate::random::Random;
+use wasm_bindgen::prelude::*;
+
+/// Seeded coherent noise, mirrored by `glsl/lib/noise.glsl`.
+///
+/// Gradient and simplex noise are in range [-1, 1], Worley noise is in range [0, 1.5].
+/// Only basic arithmetic is used, so results are the same on every platform.
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Noise {
+    permutation: [u8; 256],
+}
+
+/// Edges of a cube, Ken Perlin's "Improving Noise" gradients
+const GRADIENTS: [[f32; 3]; 12] = [
+    [1.0, 1.0, 0.0],
+    [-1.0, 1.0, 0.0],
+    [1.0, -1.0, 0.0],
+    [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0],
+    [-1.0, 0.0, 1.0],
+    [1.0, 0.0, -1.0],
+    [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0],
+    [0.0, -1.0, 1.0],
+    [0.0, 1.0, -1.0],
+    [0.0, -1.0, -1.0],
+];
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn cell(v: f32) -> (i32, f32) {
+    let i = v.floor();
+    (i as i32, v - i)
+}
+
+#[wasm_bindgen]
+impl Noise {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: i32) -> Noise {
+        Noise::with_random(Random::new(seed))
+    }
+
+    /// Permutation table as 256x1 RGBA texture content, for `noise.glsl`.
+    /// Green and blue of texel `i` hold x and y of gradient `i % 12` plus one,
+    /// so shaders read the gradients from `GRADIENTS` instead of a copy of them.
+    pub fn permutation_bytes(&self) -> Vec<u8> {
+        self.permutation
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &p)| {
+                let g = GRADIENTS[i % 12];
+                [p, (g[0] + 1.0) as u8, (g[1] + 1.0) as u8, 255]
+            })
+            .collect()
+    }
+
+    /// Gradient noise, useful for jitter along the arc length of a stroke
+    pub fn perlin1(&self, x: f32) -> f32 {
+        let (i, x) = cell(x);
+        2.0 * lerp(
+            self.gradient1(i, x),
+            self.gradient1(i + 1, x - 1.0),
+            fade(x),
+        )
+    }
+
+    pub fn perlin2(&self, x: f32, y: f32) -> f32 {
+        let (i, x) = cell(x);
+        let (j, y) = cell(y);
+        let (u, v) = (fade(x), fade(y));
+        lerp(
+            lerp(
+                self.gradient2(self.hash2(i, j), x, y),
+                self.gradient2(self.hash2(i + 1, j), x - 1.0, y),
+                u,
+            ),
+            lerp(
+                self.gradient2(self.hash2(i, j + 1), x, y - 1.0),
+                self.gradient2(self.hash2(i + 1, j + 1), x - 1.0, y - 1.0),
+                u,
+            ),
+            v,
+        )
+    }
+
+    pub fn perlin3(&self, x: f32, y: f32, z: f32) -> f32 {
+        let (i, x) = cell(x);
+        let (j, y) = cell(y);
+        let (k, z) = cell(z);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+        let corner = |di: i32, dj: i32, dk: i32| {
+            self.gradient3(
+                self.hash3(i + di, j + dj, k + dk),
+                x - di as f32,
+                y - dj as f32,
+                z - dk as f32,
+            )
+        };
+        lerp(
+            lerp(
+                lerp(corner(0, 0, 0), corner(1, 0, 0), u),
+                lerp(corner(0, 1, 0), corner(1, 1, 0), u),
+                v,
+            ),
+            lerp(
+                lerp(corner(0, 0, 1), corner(1, 0, 1), u),
+                lerp(corner(0, 1, 1), corner(1, 1, 1), u),
+                v,
+            ),
+            w,
+        )
+    }
+
+    /// Simplex noise, see Gustavson, "Simplex noise demystified"
+    pub fn simplex2(&self, x: f32, y: f32) -> f32 {
+        const F2: f32 = 0.36602542; // (sqrt(3) - 1) / 2
+        const G2: f32 = 0.21132487; // (3 - sqrt(3)) / 6
+
+        let s = (x + y) * F2;
+        let i = (x + s).floor();
+        let j = (y + s).floor();
+        let t = (i + j) * G2;
+        let x0 = x - (i - t);
+        let y0 = y - (j - t);
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+        let (i, j) = (i as i32, j as i32);
+
+        let corners = [
+            (x0, y0, 0, 0),
+            (x0 - i1 as f32 + G2, y0 - j1 as f32 + G2, i1, j1),
+            (x0 - 1.0 + 2.0 * G2, y0 - 1.0 + 2.0 * G2, 1, 1),
+        ];
+        let sum: f32 = corners
+            .iter()
+            .map(|&(x, y, di, dj)| {
+                let t = 0.5 - x * x - y * y;
+                if t < 0.0 {
+                    0.0
+                } else {
+                    let t2 = t * t;
+                    t2 * t2 * self.gradient2(self.hash2(i + di, j + dj), x, y)
+                }
+            })
+            .sum();
+        70.0 * sum
+    }
+
+    pub fn simplex3(&self, x: f32, y: f32, z: f32) -> f32 {
+        const F3: f32 = 1.0 / 3.0;
+        const G3: f32 = 1.0 / 6.0;
+
+        let s = (x + y + z) * F3;
+        let (i, j, k) = ((x + s).floor(), (y + s).floor(), (z + s).floor());
+        let t = (i + j + k) * G3;
+        let (x0, y0, z0) = (x - (i - t), y - (j - t), z - (k - t));
+        let (i, j, k) = (i as i32, j as i32, k as i32);
+
+        // Offsets of the second and the third corners of the simplex
+        let (o1, o2) = if x0 >= y0 {
+            if y0 >= z0 {
+                ([1, 0, 0], [1, 1, 0])
+            } else if x0 >= z0 {
+                ([1, 0, 0], [1, 0, 1])
+            } else {
+                ([0, 0, 1], [1, 0, 1])
+            }
+        } else if y0 < z0 {
+            ([0, 0, 1], [0, 1, 1])
+        } else if x0 < z0 {
+            ([0, 1, 0], [0, 1, 1])
+        } else {
+            ([0, 1, 0], [1, 1, 0])
+        };
+
+        let sum: f32 = [[0, 0, 0], o1, o2, [1, 1, 1]]
+            .iter()
+            .enumerate()
+            .map(|(n, o)| {
+                let g = n as f32 * G3;
+                let (x, y, z) = (
+                    x0 - o[0] as f32 + g,
+                    y0 - o[1] as f32 + g,
+                    z0 - o[2] as f32 + g,
+                );
+                let t = 0.6 - x * x - y * y - z * z;
+                if t < 0.0 {
+                    0.0
+                } else {
+                    let t2 = t * t;
+                    let hash = self.hash3(i + o[0], j + o[1], k + o[2]);
+                    t2 * t2 * self.gradient3(hash, x, y, z)
+                }
+            })
+            .sum();
+        32.0 * sum
+    }
+
+    /// Cellular noise, distance to the nearest of random points, one point in every cell
+    pub fn worley2(&self, x: f32, y: f32) -> f32 {
+        let (i, x) = cell(x);
+        let (j, y) = cell(y);
+        let mut nearest = f32::INFINITY;
+        for dj in -1..=1 {
+            for di in -1..=1 {
+                let hash = self.hash2(i + di, j + dj);
+                let fx = di as f32 + self.hash(hash as i32) as f32 / 255.0;
+                let fy = dj as f32 + self.hash(hash as i32 + 1) as f32 / 255.0;
+                let d = (fx - x) * (fx - x) + (fy - y) * (fy - y);
+                nearest = nearest.min(d);
+            }
+        }
+        nearest.sqrt()
+    }
+}
+
+impl Noise {
+    /// Noise shuffled by `random`, the permutation takes 255 values of it
+    pub(crate) fn with_random(mut random: Random) -> Noise {
+        let mut permutation = [0u8; 256];
+        for (i, p) in permutation.iter_mut().enumerate() {
+            *p = i as u8;
+        }
+        for i in (1..permutation.len()).rev() {
+            let j = (random.next_u32() % (i as u32 + 1)) as usize;
+            permutation.swap(i, j);
+        }
+        Noise { permutation }
+    }
+
+    fn hash(&self, i: i32) -> usize {
+        self.permutation[(i & 255) as usize] as usize
+    }
+    fn hash2(&self, x: i32, y: i32) -> usize {
+        self.hash(self.hash(x) as i32 + y)
+    }
+    fn hash3(&self, x: i32, y: i32, z: i32) -> usize {
+        self.hash(self.hash2(x, y) as i32 + z)
+    }
+
+    fn gradient1(&self, i: i32, x: f32) -> f32 {
+        // 16 slopes in range [-1, 1] excluding zero
+        (self.hash(i) as f32 % 16.0 - 7.5) / 7.5 * x
+    }
+    fn gradient2(&self, hash: usize, x: f32, y: f32) -> f32 {
+        let g = GRADIENTS[hash % 12];
+        g[0] * x + g[1] * y
+    }
+    fn gradient3(&self, hash: usize, x: f32, y: f32, z: f32) -> f32 {
+        let g = GRADIENTS[hash % 12];
+        g[0] * x + g[1] * y + g[2] * z
+    }
+}
+
+/// Fractal Brownian motion, sum of `octaves` noise layers normalized to the range of `noise`.
+/// `noise` receives frequency of the layer, every next layer has frequency multiplied
+/// by `lacunarity` and amplitude multiplied by `gain`.
+///
+/// `fbm(4, 2.0, 0.5, |f| noise.perlin2(x * f, y * f))`
+pub fn fbm(octaves: u32, lacunarity: f32, gain: f32, noise: impl Fn(f32) -> f32) -> f32 {
+    let mut sum = 0.0;
+    let mut total = 0.0;
+    let mut frequency = 1.0;
+    let mut amplitude = 1.0;
+    for _ in 0..octaves {
+        sum += amplitude * noise(frequency);
+        total += amplitude;
+        frequency *= lacunarity;
+        amplitude *= gain;
+    }
+    if total > 0.0 {
+        sum / total
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fbm, Noise, GRADIENTS};
+
+    fn samples() -> impl Iterator<Item = (f32, f32, f32)> {
+        (0..2000).map(|i| {
+            let i = i as f32;
+            (i * 0.137 - 50.0, i * 0.071 - 30.0, i * 0.053)
+        })
+    }
+
+    #[test]
+    fn lattice_and_range() {
+        let noise = Noise::new(3);
+        for i in -5..5 {
+            let v = i as f32;
+            assert_eq!(noise.perlin1(v), 0.0);
+            assert_eq!(noise.perlin2(v, 2.0), 0.0);
+            assert_eq!(noise.perlin3(v, 1.0, -3.0), 0.0);
+        }
+        for (x, y, z) in samples() {
+            for v in [
+                noise.perlin1(x),
+                noise.perlin2(x, y),
+                noise.perlin3(x, y, z),
+                noise.simplex2(x, y),
+                noise.simplex3(x, y, z),
+            ] {
+                assert!((-1.0..=1.0).contains(&v), "{}", v);
+            }
+            assert!((0.0..=1.5).contains(&noise.worley2(x, y)));
+        }
+    }
+
+    #[test]
+    fn seeded() {
+        assert_eq!(Noise::new(1), Noise::new(1));
+        assert_ne!(
+            Noise::new(1).simplex2(0.3, 0.7),
+            Noise::new(2).simplex2(0.3, 0.7)
+        );
+        let bytes = Noise::new(1).permutation_bytes();
+        assert_eq!(bytes.len(), 256 * 4);
+        assert_eq!(bytes[3], 255);
+    }
+
+    #[test]
+    fn continuous() {
+        let noise = Noise::new(5);
+        for (x, y, _) in samples() {
+            let (dx, dy) = (x + 0.001, y + 0.001);
+            assert!((noise.perlin2(x, y) - noise.perlin2(dx, dy)).abs() < 0.01);
+            assert!((noise.simplex2(x, y) - noise.simplex2(dx, dy)).abs() < 0.02);
+            assert!((noise.worley2(x, y) - noise.worley2(dx, dy)).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn fractal() {
+        assert_eq!(fbm(3, 2.0, 0.5, |_| 0.5), 0.5);
+        let v = fbm(3, 2.0, 0.5, |f| f);
+        assert!((v - (1.0 + 0.5 * 2.0 + 0.25 * 4.0) / 1.75).abs() < 0.0001);
+    }
+
+    #[test]
+    fn texture_gradients() {
+        let bytes = Noise::new(1).permutation_bytes();
+        for (i, texel) in bytes.chunks(4).enumerate() {
+            // Decoded like `noise_gradient2` in `glsl/lib/noise.glsl`
+            let g = [texel[1] as f32 - 1.0, texel[2] as f32 - 1.0];
+            assert_eq!(g, GRADIENTS[i % 12][..2], "texel {}", i);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use super::color::Color;
+use super::straight_color::StraightColor;
+
+/// Perceptual OKLab color with straight alpha, see https://bottosson.github.io/posts/oklab/
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct OkLab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+/// Polar form of OKLab, hue is in degrees [0, 360)
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct OkLch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+    pub alpha: f32,
+}
+
+impl OkLab {
+    pub fn new(l: f32, a: f32, b: f32, alpha: f32) -> OkLab {
+        OkLab { l, a, b, alpha }
+    }
+}
+
+impl OkLch {
+    pub fn new(l: f32, c: f32, h: f32, alpha: f32) -> OkLch {
+        OkLch { l, c, h, alpha }
+    }
+}
+
+impl From<Color> for OkLab {
+    fn from(color: Color) -> Self {
+        let c = StraightColor::from(color);
+
+        let l = (0.41222146 * c.r + 0.53633255 * c.g + 0.051445995 * c.b).cbrt();
+        let m = (0.2119035 * c.r + 0.6806995 * c.g + 0.10739696 * c.b).cbrt();
+        let s = (0.08830246 * c.r + 0.28171885 * c.g + 0.6299787 * c.b).cbrt();
+
+        OkLab {
+            l: 0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+            a: 1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+            b: 0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+            alpha: color.a,
+        }
+    }
+}
+
+impl From<OkLab> for Color {
+    fn from(c: OkLab) -> Self {
+        let l = c.l + 0.39633778 * c.a + 0.21580376 * c.b;
+        let m = c.l - 0.105561346 * c.a - 0.06385417 * c.b;
+        let s = c.l - 0.08948418 * c.a - 1.2914855 * c.b;
+
+        let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+
+        StraightColor::new(
+            4.0767417 * l - 3.3077116 * m + 0.23096994 * s,
+            -1.268438 * l + 2.6097574 * m - 0.34131938 * s,
+            -0.0041960864 * l - 0.7034186 * m + 1.7076147 * s,
+            c.alpha,
+        )
+        .into()
+    }
+}
+
+impl From<OkLab> for OkLch {
+    fn from(c: OkLab) -> Self {
+        OkLch {
+            l: c.l,
+            c: (c.a * c.a + c.b * c.b).sqrt(),
+            h: c.b.atan2(c.a).to_degrees().rem_euclid(360.0),
+            alpha: c.alpha,
+        }
+    }
+}
+
+impl From<OkLch> for OkLab {
+    fn from(c: OkLch) -> Self {
+        let (sin, cos) = c.h.to_radians().sin_cos();
+        OkLab {
+            l: c.l,
+            a: c.c * cos,
+            b: c.c * sin,
+            alpha: c.alpha,
+        }
+    }
+}
+
+impl From<Color> for OkLch {
+    fn from(color: Color) -> Self {
+        OkLab::from(color).into()
+    }
+}
+
+impl From<OkLch> for Color {
+    fn from(c: OkLch) -> Self {
+        OkLab::from(c).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OkLab, OkLch};
+    use crate::geometry::Color;
+
+    fn assert_close(a: Color, b: Color) {
+        for (x, y) in [(a.r, b.r), (a.g, b.g), (a.b, b.b), (a.a, b.a)] {
+            assert!((x - y).abs() < 0.0001, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn white() {
+        let white = OkLab::from(Color::new(1.0, 1.0, 1.0, 1.0));
+        assert!((white.l - 1.0).abs() < 0.0001);
+        assert!(white.a.abs() < 0.0001);
+        assert!(white.b.abs() < 0.0001);
+    }
+
+    #[test]
+    fn round_trip() {
+        for c in [
+            Color::new(0.2, 0.4, 0.6, 1.0),
+            Color::new(0.45, 0.05, 0.15, 0.5),
+            Color::new(0.0, 0.0, 0.0, 1.0),
+            Color::new(0.25, 0.25, 0.0, 0.25),
+        ] {
+            assert_close(Color::from(OkLab::from(c)), c);
+            assert_close(Color::from(OkLch::from(c)), c);
+        }
+    }
+}
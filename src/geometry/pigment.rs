@@ -0,0 +1,309 @@
+use super::color::Color;
+use super::mix::Mix;
+use super::straight_color::StraightColor;
+use std::sync::OnceLock;
+
+/// Number of spectral samples, 380nm to 730nm with 10nm step
+const SAMPLES: usize = 36;
+/// Lowest reflectance, pigments with zero reflectance would absorb everything they are mixed with
+const MIN_REFLECTANCE: f32 = 0.0001;
+
+type Spectrum = [f32; SAMPLES];
+type Rgb = [f32; 3];
+
+fn wavelength(i: usize) -> f32 {
+    380.0 + 10.0 * i as f32
+}
+
+/// Piecewise gaussian from Wyman, Sloan and Shirley, "Simple Analytic Approximations
+/// to the CIE XYZ Color Matching Functions"
+fn lobe(x: f32, mu: f32, sigma_low: f32, sigma_high: f32) -> f32 {
+    let t = (x - mu) / if x < mu { sigma_low } else { sigma_high };
+    (-0.5 * t * t).exp()
+}
+
+fn cie_xyz(l: f32) -> Rgb {
+    [
+        1.056 * lobe(l, 599.8, 37.9, 31.0) + 0.362 * lobe(l, 442.0, 16.0, 26.7)
+            - 0.065 * lobe(l, 501.1, 20.4, 26.2),
+        0.821 * lobe(l, 568.8, 46.9, 40.5) + 0.286 * lobe(l, 530.9, 16.3, 31.1),
+        1.217 * lobe(l, 437.0, 11.8, 36.0) + 0.681 * lobe(l, 459.0, 26.0, 13.8),
+    ]
+}
+
+fn xyz_to_linear_srgb(c: Rgb) -> Rgb {
+    [
+        3.2406 * c[0] - 1.5372 * c[1] - 0.4986 * c[2],
+        -0.9689 * c[0] + 1.8758 * c[1] + 0.0415 * c[2],
+        0.0557 * c[0] - 0.204 * c[1] + 1.057 * c[2],
+    ]
+}
+
+/// Spectral data computed once: projection of a reflectance spectrum to linear RGB,
+/// and reflectance spectra of the six base colors
+struct Tables {
+    /// Linear RGB contribution of every sample, white balanced so flat spectrum is white
+    projection: [Rgb; SAMPLES],
+    /// Cyan, magenta, yellow, red, green and blue reflectance
+    bases: [Spectrum; 6],
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let mut projection = [[0.0; 3]; SAMPLES];
+        for (i, p) in projection.iter_mut().enumerate() {
+            *p = xyz_to_linear_srgb(cie_xyz(wavelength(i)));
+        }
+        let mut white = [0.0; 3];
+        for p in projection.iter() {
+            for c in 0..3 {
+                white[c] += p[c];
+            }
+        }
+        for p in projection.iter_mut() {
+            for c in 0..3 {
+                p[c] /= white[c];
+            }
+        }
+
+        // Blue pigments reflect some green light, it's what makes blue and yellow mix to green.
+        // The difference from pure blue is compensated in `mix_straight`.
+        let bases = [
+            [0.0, 1.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [1.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.1, 1.0],
+        ]
+        .map(|target| fit_spectrum(&projection, target));
+
+        Tables { projection, bases }
+    })
+}
+
+fn project(projection: &[Rgb; SAMPLES], spectrum: &Spectrum) -> Rgb {
+    let mut result = [0.0; 3];
+    for (p, r) in projection.iter().zip(spectrum.iter()) {
+        for c in 0..3 {
+            result[c] += p[c] * r;
+        }
+    }
+    result
+}
+
+/// Sigmoid of a quadratic polynomial, a smooth reflectance spectrum in range (0, 1),
+/// see Jakob and Hanika, "A Low-Dimensional Function Space for Efficient Spectral Upsampling"
+fn sigmoid_spectrum(coefficients: Rgb) -> (Spectrum, Spectrum) {
+    let mut values = [0.0; SAMPLES];
+    let mut derivatives = [0.0; SAMPLES];
+    for i in 0..SAMPLES {
+        let t = i as f32 / (SAMPLES - 1) as f32;
+        let x = (coefficients[0] * t + coefficients[1]) * t + coefficients[2];
+        let d = (1.0 + x * x).sqrt();
+        values[i] = 0.5 + 0.5 * x / d;
+        derivatives[i] = 0.5 / (d * d * d);
+    }
+    (values, derivatives)
+}
+
+/// Finds a smooth spectrum projected to `target` with damped Gauss-Newton iterations
+fn fit_spectrum(projection: &[Rgb; SAMPLES], target: Rgb) -> Spectrum {
+    let target = target.map(|v| v.clamp(0.001, 0.999));
+    let mut coefficients = [0.0; 3];
+    for _ in 0..200 {
+        let (values, derivatives) = sigmoid_spectrum(coefficients);
+        let current = project(projection, &values);
+        let residual = [0, 1, 2].map(|c| target[c] - current[c]);
+        if residual.iter().all(|r| r.abs() < 0.00001) {
+            break;
+        }
+
+        // Jacobian of the projected color by the coefficients
+        let mut jacobian = [[0.0; 3]; 3];
+        for i in 0..SAMPLES {
+            let t = i as f32 / (SAMPLES - 1) as f32;
+            let basis = [t * t, t, 1.0];
+            for c in 0..3 {
+                for k in 0..3 {
+                    jacobian[c][k] += projection[i][c] * derivatives[i] * basis[k];
+                }
+            }
+        }
+        match solve(jacobian, residual) {
+            Some(step) => {
+                for k in 0..3 {
+                    coefficients[k] += step[k].clamp(-50.0, 50.0);
+                }
+            }
+            None => break,
+        }
+    }
+    sigmoid_spectrum(coefficients).0
+}
+
+/// Solves 3x3 linear system with Cramer's rule
+fn solve(m: [Rgb; 3], v: Rgb) -> Option<Rgb> {
+    let det = |m: [Rgb; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+    let d = det(m);
+    if d.abs() < 1e-12 {
+        return None;
+    }
+    Some([0, 1, 2].map(|k| {
+        let mut mk = m;
+        for row in 0..3 {
+            mk[row][k] = v[row];
+        }
+        det(mk) / d
+    }))
+}
+
+/// Decomposes color into white, one secondary and one primary color,
+/// and sums their reflectance spectra
+fn reflectance(c: Rgb) -> Spectrum {
+    let bases = &tables().bases;
+    let [r, g, b] = c.map(|v| v.clamp(0.0, 1.0));
+    let w = r.min(g).min(b);
+    let (r, g, b) = (r - w, g - w, b - w);
+    let cyan = g.min(b);
+    let magenta = r.min(b);
+    let yellow = r.min(g);
+    let (r, g, b) = (r - magenta - yellow, g - cyan - yellow, b - cyan - magenta);
+
+    let weights = [cyan, magenta, yellow, r, g, b];
+    let mut spectrum = [w; SAMPLES];
+    for (weight, base) in weights.iter().zip(bases.iter()) {
+        for i in 0..SAMPLES {
+            spectrum[i] += weight * base[i];
+        }
+    }
+    spectrum.map(|v| v.clamp(MIN_REFLECTANCE, 1.0))
+}
+
+/// Kubelka-Munk absorption to scattering ratio
+fn absorption(r: f32) -> f32 {
+    (1.0 - r) * (1.0 - r) / (2.0 * r)
+}
+
+fn reflectance_from_absorption(ks: f32) -> f32 {
+    1.0 + ks - (ks * ks + 2.0 * ks).sqrt()
+}
+
+/// Mixes straight linear colors with concentrations, sum of concentrations should be 1.
+///
+/// Spectrum of a color isn't projected exactly to the same color, so the projection error
+/// of every color is added back, and mixing a color with itself gives the same color.
+fn mix_straight(colors: &[(Rgb, f32)]) -> Rgb {
+    let projection = &tables().projection;
+    let mut ks = [0.0; SAMPLES];
+    let mut error = [0.0; 3];
+    for (color, concentration) in colors {
+        let spectrum = reflectance(*color);
+        let projected = project(projection, &spectrum);
+        for i in 0..SAMPLES {
+            ks[i] += concentration * absorption(spectrum[i]);
+        }
+        for c in 0..3 {
+            error[c] += concentration * (color[c] - projected[c]);
+        }
+    }
+    let mixed = project(projection, &ks.map(reflectance_from_absorption));
+    [0, 1, 2].map(|c| (mixed[c] + error[c]).clamp(0.0, 1.0))
+}
+
+/// Mixes premultiplied colors like paints, `weights` are amounts of every paint.
+/// Transparent colors contribute less pigment.
+pub fn mix_pigments(colors: &[(Color, f32)]) -> Color {
+    let alpha: f32 = colors.iter().map(|(c, w)| c.a * w).sum();
+    let total: f32 = colors.iter().map(|(_, w)| w).sum();
+    if alpha <= 0.0 || total <= 0.0 {
+        return Color::new(0.0, 0.0, 0.0, 0.0);
+    }
+    let straight: Vec<_> = colors
+        .iter()
+        .map(|(c, w)| {
+            let s = StraightColor::from(*c);
+            ([s.r, s.g, s.b], c.a * w / alpha)
+        })
+        .collect();
+    let [r, g, b] = mix_straight(&straight);
+    StraightColor::new(r, g, b, alpha / total).into()
+}
+
+/// Color mixed as a paint with Kubelka-Munk model instead of linear interpolation,
+/// so blue and yellow give green
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Pigment(pub Color);
+
+impl From<Color> for Pigment {
+    fn from(color: Color) -> Self {
+        Pigment(color)
+    }
+}
+
+impl From<Pigment> for Color {
+    fn from(pigment: Pigment) -> Self {
+        pigment.0
+    }
+}
+
+impl Mix for Pigment {
+    type Fraction = f32;
+
+    fn mix(self, other: Self, t: f32) -> Self {
+        if t <= 0.0 {
+            self
+        } else if t >= 1.0 {
+            other
+        } else {
+            Pigment(mix_pigments(&[(self.0, 1.0 - t), (other.0, t)]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mix_pigments, Pigment};
+    use crate::geometry::{Color, Mix};
+
+    fn assert_close(a: Color, b: Color) {
+        for (x, y) in [(a.r, b.r), (a.g, b.g), (a.b, b.b), (a.a, b.a)] {
+            assert!((x - y).abs() < 0.001, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn blue_and_yellow_give_green() {
+        let blue = Pigment(Color::new(0.0, 0.0, 1.0, 1.0));
+        let yellow = Pigment(Color::new(1.0, 1.0, 0.0, 1.0));
+        let green = blue.mix(yellow, 0.5).0;
+        assert!(green.g > green.r && green.g > green.b, "{:?}", green);
+    }
+
+    #[test]
+    fn same_color() {
+        for color in [
+            Color::new(0.2, 0.5, 0.7, 1.0),
+            Color::new(1.0, 1.0, 1.0, 1.0),
+            Color::new(0.0, 0.0, 0.0, 1.0),
+            Color::new(0.3, 0.1, 0.0, 0.5),
+        ] {
+            assert_close(Pigment(color).mix(Pigment(color), 0.5).0, color);
+        }
+    }
+
+    #[test]
+    fn transparent_color_adds_no_pigment() {
+        let red = Color::new(1.0, 0.0, 0.0, 1.0);
+        let transparent = Color::new(0.0, 0.0, 0.0, 0.0);
+        assert_close(
+            mix_pigments(&[(red, 1.0), (transparent, 1.0)]),
+            Color::new(0.5, 0.0, 0.0, 0.5),
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use super::curve::{Curve, Curve3};
+use super::vector::Vector;
+use crate::json::{Json, JsonError};
+use wasm_bindgen::prelude::*;
+
+#[derive(Clone, PartialEq, Debug)]
+enum Mapping {
+    Linear,
+    /// Sorted by `x`, `x` is raw pressure, `y` is the result
+    Points(Vec<Vector>),
+    Bezier(Curve3<f32>),
+}
+
+/// Maps raw tablet pressure to the pressure used by tools, both are in range [0, 1]
+#[wasm_bindgen]
+#[derive(Clone, PartialEq, Debug)]
+pub struct PressureCurve {
+    mapping: Mapping,
+}
+
+/// Number of control points produced by `calibrate`, including the ends
+const CALIBRATION_POINTS: usize = 9;
+
+#[wasm_bindgen]
+impl PressureCurve {
+    /// Pressure is used as is
+    pub fn linear() -> PressureCurve {
+        PressureCurve {
+            mapping: Mapping::Linear,
+        }
+    }
+
+    /// Piecewise linear curve, pressure below the first point or above the last one
+    /// takes the value of that point
+    /// @param points is an array [x1, y1, x2, y2, ...], x is raw pressure, y is the result
+    pub fn points(points: Vec<f32>) -> PressureCurve {
+        assert_eq!(points.len() % 2, 0);
+        PressureCurve::from_points(points.chunks(2).map(|p| Vector::new(p[0], p[1])).collect())
+    }
+
+    /// Cubic Bézier of raw pressure, values at 0 and 1 are `p0` and `p3`
+    pub fn bezier(p0: f32, p1: f32, p2: f32, p3: f32) -> PressureCurve {
+        PressureCurve {
+            mapping: Mapping::Bezier(Curve3(p0, p1, p2, p3)),
+        }
+    }
+
+    /// Builds a curve from raw pressure recorded while the user draws naturally,
+    /// so the pressure range of the user covers the whole [0, 1] range evenly,
+    /// and the maximal recorded pressure gives 1
+    /// @param samples is an array of raw pressure values
+    pub fn calibrate(samples: Vec<f32>) -> PressureCurve {
+        let mut samples: Vec<f32> = samples
+            .into_iter()
+            .filter(|p| p.is_finite() && *p > 0.0)
+            .map(|p| p.min(1.0))
+            .collect();
+        if samples.is_empty() {
+            return PressureCurve::linear();
+        }
+        samples.sort_by(|a, b| a.total_cmp(b));
+
+        let max = samples[samples.len() - 1];
+        let last = CALIBRATION_POINTS - 1;
+        let mut points = vec![Vector::new(0.0, 0.0)];
+        for i in 1..last {
+            let t = i as f32 / last as f32;
+            let x = samples[((samples.len() - 1) as f32 * t).round() as usize];
+            // Keep the curve a function, equal quantiles give a single point
+            if x > points[points.len() - 1].x && x < max {
+                points.push(Vector::new(x, t));
+            }
+        }
+        points.push(Vector::new(max, 1.0));
+        PressureCurve::from_points(points)
+    }
+
+    pub fn apply(&self, pressure: f32) -> f32 {
+        let p = pressure.clamp(0.0, 1.0);
+        let result = match &self.mapping {
+            Mapping::Linear => p,
+            Mapping::Bezier(curve) => curve.value_at(p),
+            Mapping::Points(points) => {
+                let index = points.partition_point(|v| v.x <= p);
+                match (index.checked_sub(1), points.get(index)) {
+                    (None, None) => p,
+                    (Some(i), None) => points[i].y,
+                    (None, Some(next)) => next.y,
+                    (Some(i), Some(next)) => {
+                        let previous = points[i];
+                        let t = (p - previous.x) / (next.x - previous.x);
+                        previous.y + (next.y - previous.y) * t
+                    }
+                }
+            }
+        };
+        result.clamp(0.0, 1.0)
+    }
+}
+
+impl PressureCurve {
+    pub fn from_points(mut points: Vec<Vector>) -> PressureCurve {
+        points.sort_by(|a, b| a.x.total_cmp(&b.x));
+        PressureCurve {
+            mapping: Mapping::Points(points),
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        match &self.mapping {
+            Mapping::Linear => Json::object().with("type", "linear"),
+            Mapping::Points(points) => Json::object().with("type", "points").with(
+                "points",
+                points.iter().flat_map(|p| [p.x, p.y]).collect::<Vec<_>>(),
+            ),
+            Mapping::Bezier(Curve3(p0, p1, p2, p3)) => Json::object()
+                .with("type", "bezier")
+                .with("values", vec![*p0, *p1, *p2, *p3]),
+        }
+    }
+
+    pub fn from_json(json: &Json) -> Result<PressureCurve, JsonError> {
+        let values = |key: &str| -> Result<Vec<f32>, JsonError> {
+            json.array_field(key)?
+                .iter()
+                .map(|v| {
+                    v.as_f32()
+                        .ok_or_else(|| JsonError::new(format!("\"{}\" must contain numbers", key)))
+                })
+                .collect()
+        };
+        match json.str_field("type")? {
+            "linear" => Ok(PressureCurve::linear()),
+            "points" => {
+                let points = values("points")?;
+                if points.len() % 2 != 0 {
+                    return Err(JsonError::new("\"points\" must contain pairs of numbers"));
+                }
+                Ok(PressureCurve::points(points))
+            }
+            "bezier" => match values("values")?[..] {
+                [p0, p1, p2, p3] => Ok(PressureCurve::bezier(p0, p1, p2, p3)),
+                _ => Err(JsonError::new("\"values\" must contain 4 numbers")),
+            },
+            other => Err(JsonError::new(format!("Unknown curve type \"{}\"", other))),
+        }
+    }
+}
+
+impl Default for PressureCurve {
+    fn default() -> Self {
+        PressureCurve::linear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PressureCurve;
+
+    #[test]
+    fn points() {
+        let curve = PressureCurve::points(vec![0.5, 0.25, 0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(curve.apply(0.0), 0.0);
+        assert_eq!(curve.apply(0.25), 0.125);
+        assert_eq!(curve.apply(0.75), 0.625);
+        assert_eq!(curve.apply(1.0), 1.0);
+        assert_eq!(curve.apply(2.0), 1.0);
+        assert_eq!(PressureCurve::points(vec![]).apply(0.3), 0.3);
+    }
+
+    #[test]
+    fn bezier() {
+        let soft = PressureCurve::bezier(0.0, 0.8, 1.0, 1.0);
+        assert_eq!(soft.apply(0.0), 0.0);
+        assert_eq!(soft.apply(1.0), 1.0);
+        assert!(soft.apply(0.25) > 0.25);
+        assert_eq!(PressureCurve::bezier(0.2, 0.2, 0.6, 0.6).apply(1.0), 0.6);
+    }
+
+    #[test]
+    fn calibrate() {
+        // Light hand, pressure rarely goes above 0.4
+        let samples: Vec<f32> = (0..=100).map(|i| i as f32 * 0.004).collect();
+        let curve = PressureCurve::calibrate(samples);
+        assert!((curve.apply(0.2) - 0.5).abs() < 0.01);
+        assert_eq!(curve.apply(0.4), 1.0);
+        assert!(curve.apply(0.1) < curve.apply(0.3));
+
+        assert_eq!(PressureCurve::calibrate(vec![]), PressureCurve::linear());
+        let constant = PressureCurve::calibrate(vec![0.5; 10]);
+        assert_eq!(constant.apply(0.25), 0.5);
+        assert_eq!(constant.apply(0.75), 1.0);
+    }
+}
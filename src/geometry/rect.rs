@@ -0,0 +1,157 @@
+use super::vector::Vector;
+
+/// Axis aligned rectangle, `right` and `bottom` are exclusive
+///
+/// The empty rectangle has inverted infinite bounds, so it's neutral for `union`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+impl Rect {
+    pub fn new(left: f32, top: f32, right: f32, bottom: f32) -> Rect {
+        Rect {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+    pub fn with_size(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect::new(x, y, x + width, y + height)
+    }
+    pub fn empty() -> Rect {
+        Rect::new(
+            f32::INFINITY,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::NEG_INFINITY,
+        )
+    }
+    pub fn from_point(point: Vector) -> Rect {
+        Rect::new(point.x, point.y, point.x, point.y)
+    }
+    /// Bounding box of the points, empty if there are no points
+    pub fn from_points(points: impl IntoIterator<Item = Vector>) -> Rect {
+        points
+            .into_iter()
+            .fold(Rect::empty(), |rect, point| rect.include(point))
+    }
+
+    pub fn width(&self) -> f32 {
+        (self.right - self.left).max(0.0)
+    }
+    pub fn height(&self) -> f32 {
+        (self.bottom - self.top).max(0.0)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.right < self.left || self.bottom < self.top
+    }
+    pub fn contains(&self, point: Vector) -> bool {
+        point.x >= self.left && point.x < self.right && point.y >= self.top && point.y < self.bottom
+    }
+
+    pub fn include(&self, point: Vector) -> Rect {
+        self.union(&Rect::from_point(point))
+    }
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect::new(
+            self.left.min(other.left),
+            self.top.min(other.top),
+            self.right.max(other.right),
+            self.bottom.max(other.bottom),
+        )
+    }
+    /// Returns `empty` if rectangles don't overlap
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let result = Rect::new(
+            self.left.max(other.left),
+            self.top.max(other.top),
+            self.right.min(other.right),
+            self.bottom.min(other.bottom),
+        );
+        if result.is_empty() {
+            Rect::empty()
+        } else {
+            result
+        }
+    }
+    /// Grows the rectangle by `d` in every direction, negative values shrink it
+    pub fn inflate(&self, d: f32) -> Rect {
+        if self.is_empty() {
+            *self
+        } else {
+            Rect::new(self.left - d, self.top - d, self.right + d, self.bottom + d)
+        }
+    }
+    /// Smallest rectangle with integer bounds containing this one
+    pub fn snap(&self) -> Rect {
+        if self.is_empty() {
+            *self
+        } else {
+            Rect::new(
+                self.left.floor(),
+                self.top.floor(),
+                self.right.ceil(),
+                self.bottom.ceil(),
+            )
+        }
+    }
+
+    /// Snapped bounds as [x, y, width, height], zeroes for the empty rectangle
+    pub fn to_xywh(self) -> [i32; 4] {
+        if self.is_empty() {
+            [0, 0, 0, 0]
+        } else {
+            let r = self.snap();
+            [
+                r.left as i32,
+                r.top as i32,
+                r.width() as i32,
+                r.height() as i32,
+            ]
+        }
+    }
+}
+
+impl Default for Rect {
+    fn default() -> Self {
+        Rect::empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rect;
+    use crate::geometry::Vector;
+
+    #[test]
+    fn union_with_empty() {
+        let r = Rect::new(1.0, 2.0, 3.0, 4.0);
+        assert_eq!(Rect::empty().union(&r), r);
+        assert_eq!(r.union(&Rect::empty()), r);
+    }
+
+    #[test]
+    fn intersection() {
+        let r1 = Rect::new(0.0, 0.0, 4.0, 4.0);
+        let r2 = Rect::new(2.0, 1.0, 6.0, 3.0);
+        assert_eq!(r1.intersection(&r2), Rect::new(2.0, 1.0, 4.0, 3.0));
+        assert!(r1.intersection(&Rect::new(5.0, 5.0, 6.0, 6.0)).is_empty());
+    }
+
+    #[test]
+    fn bounding_box() {
+        let r = Rect::from_points(vec![
+            Vector::new(1.5, 2.0),
+            Vector::new(-1.0, 4.25),
+            Vector::new(0.0, 0.0),
+        ]);
+        assert_eq!(r, Rect::new(-1.0, 0.0, 1.5, 4.25));
+        assert_eq!(r.inflate(1.0).to_xywh(), [-2, -1, 5, 7]);
+        assert!(Rect::from_points(vec![]).is_empty());
+    }
+}
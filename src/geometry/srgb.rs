@@ -0,0 +1,165 @@
+use super::color::{linear_to_srgb, srgb_to_linear, Color};
+use super::straight_color::StraightColor;
+
+/// Straight (not premultiplied) sRGB encoded color, all components are in range [0, 1]
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Srgb {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Srgb {
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> Srgb {
+        Srgb { r, g, b, a }
+    }
+
+    pub fn to_u8(self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a].map(|v| quantize(v, u8::MAX as f32) as u8)
+    }
+    pub fn from_u8(values: [u8; 4]) -> Srgb {
+        let [r, g, b, a] = values.map(|v| v as f32 / u8::MAX as f32);
+        Srgb { r, g, b, a }
+    }
+    pub fn to_u16(self) -> [u16; 4] {
+        [self.r, self.g, self.b, self.a].map(|v| quantize(v, u16::MAX as f32) as u16)
+    }
+    pub fn from_u16(values: [u16; 4]) -> Srgb {
+        let [r, g, b, a] = values.map(|v| v as f32 / u16::MAX as f32);
+        Srgb { r, g, b, a }
+    }
+}
+
+/// Rounds component to the nearest integer level
+fn quantize(v: f32, max: f32) -> f32 {
+    (v.clamp(0.0, 1.0) * max).round()
+}
+
+/// Premultiplied sRGB encoded color, the format of canvas drawing buffer
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct PremultipliedSrgb {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl PremultipliedSrgb {
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> PremultipliedSrgb {
+        PremultipliedSrgb { r, g, b, a }
+    }
+}
+
+impl From<StraightColor> for Srgb {
+    fn from(c: StraightColor) -> Self {
+        Srgb {
+            r: linear_to_srgb(c.r),
+            g: linear_to_srgb(c.g),
+            b: linear_to_srgb(c.b),
+            a: c.a,
+        }
+    }
+}
+
+impl From<Srgb> for StraightColor {
+    fn from(c: Srgb) -> Self {
+        StraightColor {
+            r: srgb_to_linear(c.r),
+            g: srgb_to_linear(c.g),
+            b: srgb_to_linear(c.b),
+            a: c.a,
+        }
+    }
+}
+
+impl From<Color> for Srgb {
+    fn from(color: Color) -> Self {
+        StraightColor::from(color).into()
+    }
+}
+
+impl From<Srgb> for Color {
+    fn from(c: Srgb) -> Self {
+        StraightColor::from(c).into()
+    }
+}
+
+impl From<Srgb> for PremultipliedSrgb {
+    fn from(c: Srgb) -> Self {
+        PremultipliedSrgb::new(c.r * c.a, c.g * c.a, c.b * c.a, c.a)
+    }
+}
+
+impl From<PremultipliedSrgb> for Srgb {
+    fn from(c: PremultipliedSrgb) -> Self {
+        if c.a > 0.0001 {
+            let k = 1.0 / c.a;
+            Srgb::new(c.r * k, c.g * k, c.b * k, c.a)
+        } else {
+            Srgb::new(0.0, 0.0, 0.0, c.a)
+        }
+    }
+}
+
+impl From<Color> for PremultipliedSrgb {
+    fn from(color: Color) -> Self {
+        Srgb::from(color).into()
+    }
+}
+
+impl From<PremultipliedSrgb> for Color {
+    fn from(c: PremultipliedSrgb) -> Self {
+        Srgb::from(c).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::{
+        linear_to_srgb, srgb_to_linear, Color, PremultipliedSrgb, Srgb, StraightColor,
+    };
+
+    #[test]
+    fn transfer_function() {
+        assert_eq!(srgb_to_linear(0.0), 0.0);
+        assert!((srgb_to_linear(1.0) - 1.0).abs() < 0.000001);
+        assert!((srgb_to_linear(0.5) - 0.21404114).abs() < 0.000001);
+        assert!((linear_to_srgb(0.21404114) - 0.5).abs() < 0.000001);
+        assert!((linear_to_srgb(0.002) - 0.002 * 12.92).abs() < 0.000001);
+    }
+
+    #[test]
+    fn premultiplication() {
+        let color = Color::from(StraightColor::new(1.0, 0.5, 0.0, 0.5));
+        assert_eq!(color, Color::new(0.5, 0.25, 0.0, 0.5));
+        assert_eq!(
+            StraightColor::from(color),
+            StraightColor::new(1.0, 0.5, 0.0, 0.5)
+        );
+
+        let srgb = PremultipliedSrgb::from(Srgb::new(1.0, 0.5, 0.0, 0.5));
+        assert_eq!(srgb, PremultipliedSrgb::new(0.5, 0.25, 0.0, 0.5));
+        assert_eq!(Srgb::from(srgb), Srgb::new(1.0, 0.5, 0.0, 0.5));
+    }
+
+    #[test]
+    fn round_trip_8bit() {
+        for v in 0..=255u8 {
+            let values = [v, 255 - v, v / 2, 255];
+            assert_eq!(Color::from_srgb8(values).to_srgb8(), values);
+        }
+        for a in 1..=255u8 {
+            let values = [200, 100, 50, a];
+            assert_eq!(Color::from_srgb8(values).to_srgb8()[3], a);
+        }
+    }
+
+    #[test]
+    fn round_trip_16bit() {
+        for v in (0..=u16::MAX).step_by(97) {
+            let values = [v, u16::MAX - v, v / 2, u16::MAX];
+            assert_eq!(Color::from_srgb16(values).to_srgb16(), values);
+        }
+    }
+}
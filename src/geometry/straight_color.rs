@@ -0,0 +1,34 @@
+use super::color::Color;
+
+/// Straight (not premultiplied) linear RGBA color
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct StraightColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl StraightColor {
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> StraightColor {
+        StraightColor { r, g, b, a }
+    }
+}
+
+impl From<StraightColor> for Color {
+    fn from(c: StraightColor) -> Self {
+        Color::new(c.r * c.a, c.g * c.a, c.b * c.a, c.a)
+    }
+}
+
+/// Color components of a fully transparent color are lost, it becomes transparent black
+impl From<Color> for StraightColor {
+    fn from(c: Color) -> Self {
+        if c.a > 0.0001 {
+            let k = 1.0 / c.a;
+            StraightColor::new(c.r * k, c.g * k, c.b * k, c.a)
+        } else {
+            StraightColor::new(0.0, 0.0, 0.0, c.a)
+        }
+    }
+}
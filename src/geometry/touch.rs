@@ -7,6 +7,8 @@ use std::ops;
 pub struct Touch {
     pub point: Vector,
     pub pressure: f32,
+    /// 0 when the pen is perpendicular to the surface, 1 when it lies flat
+    pub tilt: f32,
 }
 
 impl Touch {
@@ -14,8 +16,12 @@ impl Touch {
         Touch {
             point: Vector { x, y },
             pressure,
+            tilt: 0.0,
         }
     }
+    pub fn with_tilt(self, tilt: f32) -> Touch {
+        Touch { tilt, ..self }
+    }
     pub fn x(&self) -> f32 {
         self.point.x
     }
@@ -38,6 +44,7 @@ impl ops::Mul<f32> for Touch {
         Touch {
             point: self.point * v,
             pressure: self.pressure * v,
+            tilt: self.tilt * v,
         }
     }
 }
@@ -55,6 +62,7 @@ impl ops::Add<Touch> for Touch {
         Touch {
             point: self.point + v.point,
             pressure: self.pressure + v.pressure,
+            tilt: self.tilt + v.tilt,
         }
     }
 }
@@ -72,6 +80,7 @@ impl Mix for Touch {
         Touch {
             point: self.point.mix(other.point, t),
             pressure: self.pressure.mix(other.pressure, t),
+            tilt: self.tilt.mix(other.tilt, t),
         }
     }
 }
@@ -0,0 +1,243 @@
+use super::curve::{Curve0, Curve1, Curve2, Curve3};
+use super::mix::Mix;
+use super::touch::Touch;
+use super::vector::Vector;
+use num_traits::One;
+use std::ops;
+
+/// 2x3 affine transformation matrix
+///
+/// Maps point `p` to `x_axis * p.x + y_axis * p.y + origin`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub x_axis: Vector,
+    pub y_axis: Vector,
+    pub origin: Vector,
+}
+
+impl Transform {
+    /// Arguments are in the same order as canvas `setTransform(a, b, c, d, e, f)`
+    pub fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Transform {
+        Transform {
+            x_axis: Vector::new(a, b),
+            y_axis: Vector::new(c, d),
+            origin: Vector::new(e, f),
+        }
+    }
+    pub fn identity() -> Transform {
+        Transform::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+    pub fn translate(offset: Vector) -> Transform {
+        Transform::new(1.0, 0.0, 0.0, 1.0, offset.x, offset.y)
+    }
+    pub fn scale(x: f32, y: f32) -> Transform {
+        Transform::new(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+    /// Counter-clockwise rotation around the origin, angle in radians
+    pub fn rotate(angle: f32) -> Transform {
+        let (sin, cos) = angle.sin_cos();
+        Transform::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+    /// Mirror across the line passing through `point` in `direction`
+    pub fn reflect(point: Vector, direction: Vector) -> Transform {
+        let d = direction.normalize();
+        let (xx, xy, yy) = (d.x * d.x, d.x * d.y, d.y * d.y);
+        let linear = Transform::new(xx - yy, 2.0 * xy, 2.0 * xy, yy - xx, 0.0, 0.0);
+        Transform::translate(point) * linear * Transform::translate(-point)
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.x_axis.x * self.y_axis.y - self.y_axis.x * self.x_axis.y
+    }
+
+    /// Average length scale, a unit square area is multiplied by its square
+    pub fn scale_factor(&self) -> f32 {
+        self.determinant().abs().sqrt()
+    }
+
+    /// Returns `None` for degenerate transformations
+    pub fn invert(&self) -> Option<Transform> {
+        let det = self.determinant();
+        if det.abs() <= f32::EPSILON {
+            return None;
+        }
+        let k = 1.0 / det;
+        let linear = Transform::new(
+            self.y_axis.y * k,
+            -self.x_axis.y * k,
+            -self.y_axis.x * k,
+            self.x_axis.x * k,
+            0.0,
+            0.0,
+        );
+        Some(Transform {
+            origin: -linear.apply_vector(self.origin),
+            ..linear
+        })
+    }
+
+    /// Transformation applying `self` first and then `next`
+    pub fn then(self, next: Transform) -> Transform {
+        next * self
+    }
+
+    pub fn apply<T>(&self, value: T) -> T
+    where
+        Transform: ops::Mul<T, Output = T>,
+    {
+        *self * value
+    }
+
+    pub fn apply_point(&self, p: Vector) -> Vector {
+        self.apply_vector(p) + self.origin
+    }
+
+    /// Applies only the linear part, useful for directions and normals
+    pub fn apply_vector(&self, v: Vector) -> Vector {
+        self.x_axis * v.x + self.y_axis * v.y
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+impl One for Transform {
+    fn one() -> Self {
+        Transform::identity()
+    }
+}
+
+impl ops::Mul<Transform> for Transform {
+    type Output = Transform;
+
+    fn mul(self, other: Transform) -> Transform {
+        Transform {
+            x_axis: self.apply_vector(other.x_axis),
+            y_axis: self.apply_vector(other.y_axis),
+            origin: self * other.origin,
+        }
+    }
+}
+
+impl ops::Mul<Vector> for Transform {
+    type Output = Vector;
+
+    fn mul(self, v: Vector) -> Vector {
+        self.apply_point(v)
+    }
+}
+
+impl ops::Mul<Touch> for Transform {
+    type Output = Touch;
+
+    fn mul(self, touch: Touch) -> Touch {
+        Touch {
+            point: self * touch.point,
+            ..touch
+        }
+    }
+}
+
+// Bezier curves are affine invariant, so it's enough to transform control points
+
+impl<T: Mix + Clone> ops::Mul<Curve0<T>> for Transform
+where
+    Transform: ops::Mul<T, Output = T>,
+{
+    type Output = Curve0<T>;
+
+    fn mul(self, curve: Curve0<T>) -> Curve0<T> {
+        Curve0(self * curve.0)
+    }
+}
+
+impl<T: Mix + Clone> ops::Mul<Curve1<T>> for Transform
+where
+    Transform: ops::Mul<T, Output = T>,
+{
+    type Output = Curve1<T>;
+
+    fn mul(self, curve: Curve1<T>) -> Curve1<T> {
+        Curve1(self * curve.0, self * curve.1)
+    }
+}
+
+impl<T: Mix + Clone> ops::Mul<Curve2<T>> for Transform
+where
+    Transform: ops::Mul<T, Output = T>,
+{
+    type Output = Curve2<T>;
+
+    fn mul(self, curve: Curve2<T>) -> Curve2<T> {
+        Curve2(self * curve.0, self * curve.1, self * curve.2)
+    }
+}
+
+impl<T: Mix + Clone> ops::Mul<Curve3<T>> for Transform
+where
+    Transform: ops::Mul<T, Output = T>,
+{
+    type Output = Curve3<T>;
+
+    fn mul(self, curve: Curve3<T>) -> Curve3<T> {
+        Curve3(
+            self * curve.0,
+            self * curve.1,
+            self * curve.2,
+            self * curve.3,
+        )
+    }
+}
+
+impl From<[f32; 6]> for Transform {
+    fn from(v: [f32; 6]) -> Self {
+        Transform::new(v[0], v[1], v[2], v[3], v[4], v[5])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transform;
+    use crate::geometry::{Distance, Touch, Vector};
+    use std::f32::consts::PI;
+
+    #[test]
+    fn compose() {
+        let t = Transform::translate(Vector::new(1.0, 2.0)) * Transform::scale(2.0, 3.0);
+        assert_eq!(t * Vector::new(1.0, 1.0), Vector::new(3.0, 5.0));
+        assert_eq!(
+            Transform::scale(2.0, 3.0).then(Transform::translate(Vector::new(1.0, 2.0))),
+            t
+        );
+    }
+
+    #[test]
+    fn rotate() {
+        let v = Transform::rotate(PI / 2.0) * Vector::new(1.0, 0.0);
+        assert!(v.distance(Vector::new(0.0, 1.0)) < 0.0001);
+    }
+
+    #[test]
+    fn invert() {
+        let t = Transform::new(1.0, 2.0, -3.0, 4.0, 5.0, -6.0);
+        let p = Vector::new(7.0, -8.0);
+        let back = t.invert().unwrap() * (t * p);
+        assert!(back.distance(p) < 0.0001);
+        assert_eq!(Transform::scale(0.0, 1.0).invert(), None);
+    }
+
+    #[test]
+    fn reflect() {
+        let t = Transform::reflect(Vector::new(1.0, 0.0), Vector::new(0.0, 1.0));
+        assert!((t * Vector::new(3.0, 2.0)).distance(Vector::new(-1.0, 2.0)) < 0.0001);
+    }
+
+    #[test]
+    fn keeps_pressure() {
+        let touch = Transform::scale(2.0, 2.0) * Touch::new(1.0, 1.0, 0.5);
+        assert_eq!(touch, Touch::new(2.0, 2.0, 0.5));
+    }
+}
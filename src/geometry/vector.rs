@@ -25,8 +25,14 @@ impl Vector {
     pub fn length(self) -> f32 {
         (self * self).sqrt()
     }
+    /// Unit vector in the same direction, zero for vectors too short to have a direction
     pub fn normalize(self) -> Vector {
-        self / self.length()
+        let length = self.length();
+        if length > f32::EPSILON {
+            self / length
+        } else {
+            Vector::zero()
+        }
     }
     pub fn normal(self) -> Vector {
         Vector {
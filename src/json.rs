@@ -0,0 +1,475 @@
+use std::fmt;
+use wasm_bindgen::JsValue;
+
+/// Minimal JSON document model, used for tool settings and presets
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// Keys keep the order of insertion
+    Object(Vec<(String, Json)>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonError {
+    pub message: String,
+}
+
+impl JsonError {
+    pub fn new(message: impl Into<String>) -> JsonError {
+        JsonError {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<JsonError> for JsValue {
+    fn from(error: JsonError) -> Self {
+        JsValue::from_str(&error.message)
+    }
+}
+
+impl Json {
+    pub fn object() -> Json {
+        Json::Object(Vec::new())
+    }
+
+    /// Adds a field to an object, does nothing for other values
+    pub fn with(mut self, key: &str, value: impl Into<Json>) -> Json {
+        if let Json::Object(fields) = &mut self {
+            fields.push((key.to_string(), value.into()));
+        }
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(v) => Some(*v),
+            _ => None,
+        }
+    }
+    pub fn as_f32(&self) -> Option<f32> {
+        self.as_f64().map(|v| v as f32)
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(v) => Some(v),
+            _ => None,
+        }
+    }
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Field of an object, error if it's missing
+    pub fn field(&self, key: &str) -> Result<&Json, JsonError> {
+        self.get(key)
+            .ok_or_else(|| JsonError::new(format!("Missing field \"{}\"", key)))
+    }
+    pub fn f32_field(&self, key: &str) -> Result<f32, JsonError> {
+        self.field(key)?
+            .as_f32()
+            .ok_or_else(|| JsonError::new(format!("Field \"{}\" must be a number", key)))
+    }
+    pub fn str_field(&self, key: &str) -> Result<&str, JsonError> {
+        self.field(key)?
+            .as_str()
+            .ok_or_else(|| JsonError::new(format!("Field \"{}\" must be a string", key)))
+    }
+    pub fn array_field(&self, key: &str) -> Result<&[Json], JsonError> {
+        self.field(key)?
+            .as_array()
+            .ok_or_else(|| JsonError::new(format!("Field \"{}\" must be an array", key)))
+    }
+
+    pub fn parse(text: &str) -> Result<Json, JsonError> {
+        let mut parser = Parser {
+            chars: text.chars().collect(),
+            position: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.position < parser.chars.len() {
+            return Err(parser.error("Unexpected trailing characters"));
+        }
+        Ok(value)
+    }
+}
+
+impl From<f32> for Json {
+    fn from(v: f32) -> Self {
+        Json::Number(v as f64)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(v: f64) -> Self {
+        Json::Number(v)
+    }
+}
+
+impl From<u32> for Json {
+    fn from(v: u32) -> Self {
+        Json::Number(v as f64)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(v: bool) -> Self {
+        Json::Bool(v)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(v: &str) -> Self {
+        Json::String(v.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(v: String) -> Self {
+        Json::String(v)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(v: Vec<T>) -> Self {
+        Json::Array(v.into_iter().map(|v| v.into()).collect())
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Compact JSON, numbers that aren't finite are written as `null`
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(v) => write!(f, "{}", v),
+            Json::Number(v) if v.is_finite() => write!(f, "{}", v),
+            Json::Number(_) => write!(f, "null"),
+            Json::String(v) => write_string(f, v),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Deeper documents are rejected, so nested arrays in untrusted input can't overflow the stack
+const MAX_DEPTH: usize = 64;
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    /// Number of arrays and objects around the current value
+    depth: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> JsonError {
+        JsonError::new(format!("{} at {}", message, self.position))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.position += 1;
+        c
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\n' | '\r' | '\t')) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, word: &str) -> Result<(), JsonError> {
+        for c in word.chars() {
+            if self.next() != Some(c) {
+                return Err(self.error(&format!("Expected \"{}\"", word)));
+            }
+        }
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('n') => self.expect("null").map(|_| Json::Null),
+            Some('t') => self.expect("true").map(|_| Json::Bool(true)),
+            Some('f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some('"') => self.string().map(Json::String),
+            Some('[') => self.nested(Parser::array),
+            Some('{') => self.nested(Parser::object),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            _ => Err(self.error("Unexpected character")),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut Parser) -> Result<Json, JsonError>,
+    ) -> Result<Json, JsonError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error("Too deep nesting"));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Skips digits, returns their count
+    fn digits(&mut self) -> usize {
+        let start = self.position;
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        self.position - start
+    }
+
+    /// `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.position;
+        if self.peek() == Some('-') {
+            self.position += 1;
+        }
+        let leading_zero = self.peek() == Some('0');
+        let integer = self.digits();
+        if integer == 0 || (leading_zero && integer > 1) {
+            return Err(self.error("Invalid number"));
+        }
+        if self.peek() == Some('.') {
+            self.position += 1;
+            if self.digits() == 0 {
+                return Err(self.error("Invalid number"));
+            }
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            self.position += 1;
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.position += 1;
+            }
+            if self.digits() == 0 {
+                return Err(self.error("Invalid number"));
+            }
+        }
+        let text: String = self.chars[start..self.position].iter().collect();
+        text.parse()
+            .map(Json::Number)
+            .map_err(|_| self.error("Invalid number"))
+    }
+
+    fn hex(&mut self) -> Result<u32, JsonError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .next()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error("Invalid escape"))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect("\"")?;
+        let mut result = String::new();
+        loop {
+            match self.next() {
+                None => return Err(self.error("Unterminated string")),
+                Some('"') => return Ok(result),
+                Some('\\') => {
+                    let c = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let mut code = self.hex()?;
+                            // Surrogate pair
+                            if (0xd800..0xdc00).contains(&code) {
+                                self.expect("\\u")?;
+                                let low = self.hex()?;
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return Err(self.error("Invalid escape"));
+                                }
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                            }
+                            char::from_u32(code).ok_or_else(|| self.error("Invalid escape"))?
+                        }
+                        _ => return Err(self.error("Invalid escape")),
+                    };
+                    result.push(c);
+                }
+                Some(c) => result.push(c),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, JsonError> {
+        self.expect("[")?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.position += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(items)),
+                _ => return Err(self.error("Expected \",\" or \"]\"")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, JsonError> {
+        self.expect("{")?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.position += 1;
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(":")?;
+            fields.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(fields)),
+                _ => return Err(self.error("Expected \",\" or \"}\"")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Json;
+
+    #[test]
+    fn round_trip() {
+        let value = Json::object()
+            .with("name", "wave \"pencil\"\n")
+            .with("size", 1.5f32)
+            .with("enabled", true)
+            .with("values", vec![1.0f32, -2.5e-3])
+            .with("nothing", Json::Null)
+            .with("empty", Json::object());
+        let text = value.to_string();
+        assert_eq!(
+            text,
+            r#"{"name":"wave \"pencil\"\n","size":1.5,"enabled":true,"values":[1,-0.0024999999441206455],"nothing":null,"empty":{}}"#
+        );
+        assert_eq!(Json::parse(&text), Ok(value));
+    }
+
+    #[test]
+    fn parse() {
+        let value = Json::parse(r#" { "a" : [ 1 , 2e2, { } ], "b": "é😀" } "#).unwrap();
+        assert_eq!(value.array_field("a").unwrap()[1], Json::Number(200.0));
+        assert_eq!(value.str_field("b"), Ok("é😀"));
+        assert_eq!(Json::parse(r#""\ud83d\ude00\t""#), Ok(Json::from("😀\t")));
+        assert!(value.f32_field("c").is_err());
+
+        assert!(Json::parse("[1, 2").is_err());
+        assert!(Json::parse("{\"a\": 1} x").is_err());
+        assert!(Json::parse("tru").is_err());
+        // Low surrogate out of range
+        assert!(Json::parse(r#""\ud800\u0000""#).is_err());
+        assert!(Json::parse(r#""\ud800\ud800""#).is_err());
+    }
+
+    #[test]
+    fn numbers() {
+        for text in ["0", "-0", "12", "1.5", "-0.25e-3", "2E+2", "1e5"] {
+            assert!(Json::parse(text).is_ok(), "{}", text);
+        }
+        for text in [
+            "1.", "+1", ".5", "01", "-", "1e", "1e+", "1.e3", "--1", "1-2",
+        ] {
+            assert!(Json::parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn depth() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(Json::parse(&nested(64)).is_ok());
+        assert!(Json::parse(&nested(65)).is_err());
+        assert!(Json::parse(&"[{\"a\":".repeat(100_000)).is_err());
+    }
+}
@@ -1,8 +1,16 @@
+mod color_space;
 mod context;
+mod dynamics;
 mod frame;
 mod geometry;
+mod json;
 mod path;
 mod pool;
+mod preset;
+mod quick_shape;
+mod random;
+mod stroke;
+mod symmetry;
 mod tool;
 mod tools;
 mod touch_list;
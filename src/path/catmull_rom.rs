@@ -0,0 +1,303 @@
+use crate::geometry::{Distance, Mix};
+use crate::path::WithNeighboursIterator;
+use num_traits::{one, zero, Float, NumCast, ToPrimitive};
+use std::mem::replace;
+
+/// Four control points of a centripetal Catmull-Rom segment between `p1` and `p2`,
+/// with their knot values.
+#[derive(Debug, Clone)]
+struct Segment<T: Distance> {
+    points: [T; 4],
+    knots: [T::Output; 4],
+}
+
+impl<T> Segment<T>
+where
+    T: Mix + Distance + Clone,
+    T::Output: Into<T::Fraction>,
+{
+    fn new(p0: Option<T>, p1: T, p2: T, p3: Option<T>) -> Self {
+        let reflect = -one::<T::Fraction>();
+        // Missing or coincident neighbours are replaced by a reflection of the segment,
+        // otherwise knot intervals collapse to zero.
+        let p0 = p0
+            .filter(|p0| p0.clone().distance(p1.clone()) > zero())
+            .unwrap_or_else(|| p1.clone().mix(p2.clone(), reflect));
+        let p3 = p3
+            .filter(|p3| p2.clone().distance(p3.clone()) > zero())
+            .unwrap_or_else(|| p2.clone().mix(p1.clone(), reflect));
+
+        let t0 = zero();
+        let t1 = t0 + p0.clone().distance(p1.clone()).sqrt();
+        let t2 = t1 + p1.clone().distance(p2.clone()).sqrt();
+        let t3 = t2 + p2.clone().distance(p3.clone()).sqrt();
+
+        Self {
+            points: [p0, p1, p2, p3],
+            knots: [t0, t1, t2, t3],
+        }
+    }
+
+    /// Barry-Goldman pyramidal evaluation, `u` goes from `0` at `p1` to `1` at `p2`
+    fn value_at(&self, u: T::Output) -> T {
+        let [p0, p1, p2, p3] = self.points.clone();
+        let [t0, t1, t2, t3] = self.knots;
+        let t = t1 + (t2 - t1) * u;
+        let lerp =
+            |a: T, b: T, ta: T::Output, tb: T::Output| a.mix(b, ((t - ta) / (tb - ta)).into());
+
+        let a1 = lerp(p0, p1.clone(), t0, t1);
+        let a2 = lerp(p1, p2.clone(), t1, t2);
+        let a3 = lerp(p2, p3, t2, t3);
+
+        let b1 = lerp(a1, a2.clone(), t0, t2);
+        let b2 = lerp(a2, a3, t1, t3);
+
+        lerp(b1, b2, t1, t2)
+    }
+}
+
+/// Interpolates input points with a centripetal Catmull-Rom spline.
+/// Every input point is kept, extra points are inserted so that the distance
+/// between neighbours is not greater than `step`.
+pub struct CatmullRomIterator<I>
+where
+    I: Iterator,
+    I::Item: Mix + Distance + Clone,
+{
+    iterator: WithNeighboursIterator<I>,
+    previous: Option<I::Item>,
+    segment: Option<Segment<I::Item>>,
+    index: usize,
+    count: usize,
+    step: <I::Item as Distance>::Output,
+    closing: Option<Closing<I::Item>>,
+}
+
+/// State of the segment from the last point of a closed path back to the first one
+struct Closing<T> {
+    /// The first point and its next neighbour
+    first: Option<(T, Option<T>)>,
+    last: Option<T>,
+    /// The closing segment is being emitted, it ends before the first point
+    started: bool,
+}
+
+impl<I> CatmullRomIterator<I>
+where
+    I: Iterator,
+    I::Item: Mix + Distance + Clone,
+    <I::Item as Distance>::Output: Into<<I::Item as Mix>::Fraction>,
+{
+    pub fn new(iterator: I, step: <I::Item as Distance>::Output) -> Self {
+        Self {
+            iterator: WithNeighboursIterator::new(iterator),
+            previous: None,
+            segment: None,
+            index: 0,
+            count: 0,
+            step,
+            closing: None,
+        }
+    }
+
+    /// Interpolates a closed path, the curve goes from the last point back to the first one,
+    /// without repeating the first point
+    pub fn closed(iterator: I, step: <I::Item as Distance>::Output) -> Self {
+        Self {
+            iterator: WithNeighboursIterator::closed(iterator),
+            previous: None,
+            segment: None,
+            index: 0,
+            count: 0,
+            step,
+            closing: Some(Closing {
+                first: None,
+                last: None,
+                started: false,
+            }),
+        }
+    }
+
+    fn start_segment(
+        &mut self,
+        p0: Option<I::Item>,
+        p1: I::Item,
+        p2: I::Item,
+        p3: Option<I::Item>,
+    ) -> bool {
+        let length = p1.clone().distance(p2.clone());
+        if length <= zero() {
+            return false;
+        }
+        self.count = (length / self.step).ceil().to_usize().unwrap_or(1).max(1);
+        self.index = 0;
+        self.segment = Some(Segment::new(p0, p1, p2, p3));
+        true
+    }
+}
+
+impl<I> Iterator for CatmullRomIterator<I>
+where
+    I: Iterator,
+    I::Item: Mix + Distance + Clone,
+    <I::Item as Distance>::Output: Into<<I::Item as Mix>::Fraction>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(segment) = &self.segment {
+            self.index += 1;
+            if self.index < self.count {
+                let u = <<I::Item as Distance>::Output as NumCast>::from(self.index)?
+                    / <<I::Item as Distance>::Output as NumCast>::from(self.count)?;
+                return Some(segment.value_at(u));
+            }
+            let end = segment.points[2].clone();
+            self.segment = None;
+            if matches!(self.closing, Some(Closing { started: true, .. })) {
+                return None;
+            }
+            return Some(end);
+        }
+
+        let (p1, p2, p3) = match self.iterator.next() {
+            Some(point) => point,
+            None => {
+                let closing = self.closing.as_mut()?;
+                let (first, next) = closing.first.take()?;
+                let last = closing.last.take()?;
+                closing.started = true;
+                let p0 = self.previous.take();
+                return if self.start_segment(p0, last, first, next) {
+                    self.next()
+                } else {
+                    None
+                };
+            }
+        };
+        let p0 = replace(&mut self.previous, p1.clone());
+
+        if let Some(closing) = &mut self.closing {
+            closing.last = Some(p2.clone());
+            // The segment to the first point goes last
+            if closing.first.is_none() {
+                closing.first = Some((p2.clone(), p3));
+                return Some(p2);
+            }
+        }
+
+        match p1 {
+            Some(p1) => {
+                if self.start_segment(p0, p1, p2.clone(), p3) {
+                    self.next()
+                } else {
+                    Some(p2)
+                }
+            }
+            None => Some(p2),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CatmullRomIterator;
+    use crate::geometry::{Distance, Touch, Vector};
+
+    fn assert_close(actual: Vec<Vector>, expected: Vec<Vector>) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (a, e) in actual.into_iter().zip(expected) {
+            assert!(a.distance(e) < 0.0001, "{:?} != {:?}", a, e);
+        }
+    }
+
+    #[test]
+    fn single() {
+        let points: Vec<_> =
+            CatmullRomIterator::new(vec![Vector::new(1.0, 1.0)].into_iter(), 0.5).collect();
+        assert_eq!(points, vec![Vector::new(1.0, 1.0)]);
+    }
+
+    #[test]
+    fn straight_line() {
+        let points: Vec<_> = CatmullRomIterator::new(
+            vec![
+                Vector::new(0.0, 0.0),
+                Vector::new(1.0, 0.0),
+                Vector::new(2.0, 0.0),
+            ]
+            .into_iter(),
+            0.5,
+        )
+        .collect();
+
+        assert_close(
+            points,
+            vec![
+                Vector::new(0.0, 0.0),
+                Vector::new(0.5, 0.0),
+                Vector::new(1.0, 0.0),
+                Vector::new(1.5, 0.0),
+                Vector::new(2.0, 0.0),
+            ],
+        );
+    }
+
+    #[test]
+    fn passes_through_input() {
+        let input = vec![
+            Touch::new(0.0, 0.0, 0.2),
+            Touch::new(10.0, 0.0, 0.4),
+            Touch::new(10.0, 10.0, 0.6),
+            Touch::new(0.0, 10.0, 0.8),
+        ];
+        let points: Vec<_> = CatmullRomIterator::new(input.clone().into_iter(), 1.0).collect();
+
+        for touch in input {
+            assert!(points.contains(&touch));
+        }
+        for (p1, p2) in points.iter().zip(points.iter().skip(1)) {
+            assert!(p1.distance(*p2) <= 1.5);
+            assert!(p2.pressure >= p1.pressure - 0.0001);
+        }
+    }
+
+    #[test]
+    fn coincident_points() {
+        let points: Vec<_> = CatmullRomIterator::new(
+            vec![
+                Vector::new(0.0, 0.0),
+                Vector::new(0.0, 0.0),
+                Vector::new(1.0, 0.0),
+                Vector::new(1.0, 0.0),
+            ]
+            .into_iter(),
+            0.5,
+        )
+        .collect();
+
+        assert!(points.iter().all(|p| p.x.is_finite() && p.y.is_finite()));
+        assert_eq!(points.last(), Some(&Vector::new(1.0, 0.0)));
+    }
+
+    #[test]
+    fn closed() {
+        let square = vec![
+            Vector::new(0.0, 0.0),
+            Vector::new(4.0, 0.0),
+            Vector::new(4.0, 4.0),
+            Vector::new(0.0, 4.0),
+        ];
+        let points: Vec<_> = CatmullRomIterator::closed(square.clone().into_iter(), 1.0).collect();
+
+        assert_eq!(points.first(), Some(&square[0]));
+        // The curve goes back towards the first point without repeating it
+        assert_ne!(points.last(), Some(&square[3]));
+        assert!(points.last().unwrap().distance(square[0]) <= 1.5);
+        assert!(points.iter().filter(|p| square.contains(p)).count() == 4);
+        // Symmetric neighbours at the seam
+        let (after, before) = (points[1], points[points.len() - 1]);
+        assert!((after.x - before.y).abs() < 1e-4 && (after.y - before.x).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,359 @@
+use crate::geometry::{Mix, Touch, Vector};
+use crate::path::{WithOffset, WithOffsetIterator};
+use crate::touch_list::TouchList;
+use std::collections::VecDeque;
+use wasm_bindgen::prelude::*;
+
+/// Pressure below this value scales the pattern as this value, so it never shrinks to nothing
+const MIN_PRESSURE_SCALE: f32 = 0.05;
+/// Shorter patterns, in screen pixels, are stretched to this length, dashes can't be told
+/// apart anyway and too short lengths would get lost in float precision
+const MIN_PERIOD: f32 = 1.0;
+/// Length of dots, in screen pixels, zero length dashes would give tools nothing to draw
+const DOT_LENGTH: f32 = 1.0;
+
+/// Lengths of alternating dashes and gaps
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DashPattern {
+    lengths: Vec<f32>,
+    /// Length of the pattern skipped at the stroke start
+    phase: f32,
+    /// Lengths are multiplied by the pressure at the start of every dash and gap
+    pressure_scale: bool,
+}
+
+#[wasm_bindgen]
+impl DashPattern {
+    /// Zero length dashes give dots, short dashes along the path
+    /// @param lengths is an array [dash1, gap1, dash2, gap2, ...] in path units
+    #[wasm_bindgen(constructor)]
+    pub fn new(lengths: Vec<f32>, phase: f32, pressure_scale: bool) -> DashPattern {
+        assert!(!lengths.is_empty());
+        assert_eq!(lengths.len() % 2, 0);
+        let lengths: Vec<f32> = lengths.into_iter().map(|l| l.max(0.0)).collect();
+        assert!(lengths.iter().sum::<f32>() > 0.0);
+        DashPattern {
+            lengths,
+            phase,
+            pressure_scale,
+        }
+    }
+}
+
+impl DashPattern {
+    /// Dashes of the path, each one is a separate open path,
+    /// dashes of closed paths continue over the segment back to the first touch
+    /// and a dash going over the first touch isn't split there
+    pub fn apply(&self, path: &TouchList) -> Vec<TouchList> {
+        self.dashes(path)
+            .into_iter()
+            .map(|dash| dash.path(path))
+            .collect()
+    }
+
+    /// Same as `apply`, with positions of dashes along the path
+    pub fn dashes(&self, path: &TouchList) -> Vec<Dash> {
+        let closing = path.first().cloned().filter(|_| path.closed());
+        let mut dashes: Vec<Dash> = DashIterator::new(
+            path.iter().cloned().chain(closing),
+            self.clone(),
+            path.pixel_size(),
+        )
+        .collect();
+
+        if let Some(first) = closing {
+            let at_first =
+                |touch: Option<&Touch>| matches!(touch, Some(t) if t.point == first.point);
+            if dashes.len() > 1
+                && at_first(dashes[0].touches.first())
+                && at_first(dashes[dashes.len() - 1].touches.last())
+            {
+                if let Some(mut merged) = dashes.pop() {
+                    merged.touches.extend(dashes[0].touches.iter().skip(1));
+                    dashes[0] = merged;
+                }
+            }
+        }
+        dashes
+    }
+
+    fn period(&self) -> f32 {
+        self.lengths.iter().sum()
+    }
+
+    /// Multiplier of lengths at `touch`, the period is never shorter than `min_period`
+    fn scale(&self, touch: &Touch, min_period: f32) -> f32 {
+        let scale = if self.pressure_scale {
+            touch.pressure.max(MIN_PRESSURE_SCALE)
+        } else {
+            1.0
+        };
+        scale.max(min_period / self.period())
+    }
+}
+
+/// Dash of a path, see `DashIterator`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dash {
+    pub touches: Vec<Touch>,
+    /// Index of the last path touch at or before the dash start
+    pub index: usize,
+    /// Length of the path up to the dash start
+    pub offset: f32,
+}
+
+impl Dash {
+    /// Open path of the dash with the settings of the `source` path
+    pub fn path(&self, source: &TouchList) -> TouchList {
+        let mut result = source.clone();
+        result.set_closed(false);
+        result.clear();
+        result.extend(self.touches.iter().cloned());
+        result
+    }
+}
+
+/// Splits a path into dashes, every dash is emitted as a separate list of touches
+/// starting and ending at the dash ends, so tools draw caps at both of them
+pub struct DashIterator<I>
+where
+    I: Iterator<Item = Touch>,
+{
+    iterator: WithOffsetIterator<I>,
+    pattern: DashPattern,
+    min_period: f32,
+    dot_length: f32,
+    /// Index of the current pattern element, even indices are dashes
+    index: usize,
+    /// Length left in the current pattern element
+    left: f32,
+    previous: Option<WithOffset<Touch>>,
+    /// Index of `previous` in the path
+    previous_index: usize,
+    /// Direction of the last path segment
+    direction: Vector,
+    dash: Dash,
+    ready: VecDeque<Dash>,
+}
+
+impl<I> DashIterator<I>
+where
+    I: Iterator<Item = Touch>,
+{
+    /// @param pixel_size is the size of a screen pixel in path units
+    pub fn new(iterator: I, pattern: DashPattern, pixel_size: f32) -> Self {
+        Self {
+            iterator: WithOffsetIterator::new(iterator),
+            min_period: MIN_PERIOD * pixel_size.max(f32::EPSILON),
+            dot_length: DOT_LENGTH * pixel_size,
+            pattern,
+            index: 0,
+            left: 0.0,
+            previous: None,
+            previous_index: 0,
+            direction: Vector::new(0.0, 0.0),
+            dash: Dash {
+                touches: Vec::new(),
+                index: 0,
+                offset: 0.0,
+            },
+            ready: VecDeque::new(),
+        }
+    }
+
+    fn is_dash(&self) -> bool {
+        self.index & 1 == 0
+    }
+
+    fn start(&mut self, touch: Touch) {
+        let scale = self.pattern.scale(&touch, self.min_period);
+        let mut phase = self.pattern.phase.rem_euclid(self.pattern.period()) * scale;
+        self.index = 0;
+        self.left = self.pattern.lengths[0] * scale;
+        while phase > 0.0 && phase >= self.left {
+            phase -= self.left;
+            self.index = (self.index + 1) % self.pattern.lengths.len();
+            self.left = self.pattern.lengths[self.index] * scale;
+        }
+        self.left -= phase;
+        if self.is_dash() {
+            self.dash.touches.push(touch);
+        }
+    }
+
+    /// Emits the current dash, a dash without length becomes a dot along the path
+    fn finish(&mut self) {
+        let mut dash = std::mem::replace(
+            &mut self.dash,
+            Dash {
+                touches: Vec::new(),
+                index: 0,
+                offset: 0.0,
+            },
+        );
+        let last = match dash.touches.last() {
+            Some(last) => *last,
+            None => return,
+        };
+        if dash.touches.iter().all(|t| t.point == last.point) {
+            let half = self.direction * (self.dot_length * 0.5);
+            dash.touches = vec![
+                Touch {
+                    point: last.point - half,
+                    ..last
+                },
+                Touch {
+                    point: last.point + half,
+                    ..last
+                },
+            ];
+        }
+        self.ready.push_back(dash);
+    }
+
+    /// Ends the current pattern element at `touch`, `offset` along the path
+    fn switch(&mut self, touch: Touch, offset: f32) {
+        if self.is_dash() {
+            self.dash.touches.push(touch);
+            self.finish();
+        }
+        self.index = (self.index + 1) % self.pattern.lengths.len();
+        self.left = self.pattern.lengths[self.index] * self.pattern.scale(&touch, self.min_period);
+        if self.is_dash() {
+            self.dash.touches.push(touch);
+            self.dash.index = self.previous_index;
+            self.dash.offset = offset;
+        }
+    }
+
+    fn add(&mut self, previous: WithOffset<Touch>, point: WithOffset<Touch>) {
+        let length = point.offset - previous.offset;
+        if length > 0.0 {
+            self.direction = (point.point.point - previous.point.point).normalize();
+        }
+        let mut position = 0.0;
+        while length > 0.0 && position + self.left <= length {
+            position += self.left;
+            self.switch(
+                previous.point.mix(point.point, position / length),
+                previous.offset + position,
+            );
+        }
+        self.left -= length - position;
+        if self.is_dash() && position < length {
+            self.dash.touches.push(point.point);
+        }
+    }
+}
+
+impl<I> Iterator for DashIterator<I>
+where
+    I: Iterator<Item = Touch>,
+{
+    type Item = Dash;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.ready.is_empty() {
+            match self.iterator.next() {
+                Some(point) => {
+                    match self.previous {
+                        Some(previous) => {
+                            self.add(previous, point);
+                            self.previous_index += 1;
+                        }
+                        None => self.start(point.point),
+                    }
+                    self.previous = Some(point);
+                }
+                None => {
+                    // A dash started at the path end has no length, unlike a finished dot
+                    let first = self.dash.touches.first()?.point;
+                    if self.left > 0.0 && self.dash.touches.iter().all(|t| t.point == first) {
+                        return None;
+                    }
+                    self.finish();
+                }
+            }
+        }
+        self.ready.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DashPattern;
+    use crate::touch_list::TouchList;
+
+    fn line(pressure: f32) -> TouchList {
+        let mut path = TouchList::new(1.0);
+        path.push(0.0, 0.0, pressure);
+        path.push(5.0, 0.0, pressure);
+        path.push(10.0, 0.0, pressure);
+        path
+    }
+
+    fn ends(dashes: &[TouchList]) -> Vec<(f32, f32)> {
+        dashes
+            .iter()
+            .map(|d| (d[0].x(), d[d.len() - 1].x()))
+            .collect()
+    }
+
+    #[test]
+    fn dashes() {
+        let dashes = DashPattern::new(vec![2.0, 1.0], 0.0, false).apply(&line(0.5));
+        assert_eq!(
+            ends(&dashes),
+            vec![(0.0, 2.0), (3.0, 5.0), (6.0, 8.0), (9.0, 10.0)]
+        );
+        assert_eq!(dashes[1].len(), 2);
+        assert_eq!(dashes[2][0].pressure, 0.5);
+
+        // Dashes know where they start on the source path
+        let starts: Vec<_> = DashPattern::new(vec![2.0, 1.0], 0.0, false)
+            .dashes(&line(0.5))
+            .iter()
+            .map(|d| (d.index, d.offset))
+            .collect();
+        assert_eq!(starts, vec![(0, 0.0), (0, 3.0), (1, 6.0), (1, 9.0)]);
+
+        let shifted = DashPattern::new(vec![2.0, 1.0], 2.5, false).apply(&line(0.5));
+        assert_eq!(
+            ends(&shifted),
+            vec![(0.5, 2.5), (3.5, 5.5), (6.5, 8.5), (9.5, 10.0)]
+        );
+        // The dash over the middle touch keeps it
+        assert_eq!(shifted[1].len(), 3);
+        assert_eq!(shifted[1][1].x(), 5.0);
+    }
+
+    #[test]
+    fn dots_and_pressure() {
+        let dots = DashPattern::new(vec![0.0, 4.0], 0.0, false).apply(&line(1.0));
+        // Dots are one pixel long, so tools have a direction to draw them
+        assert_eq!(ends(&dots), vec![(-0.5, 0.5), (3.5, 4.5), (7.5, 8.5)]);
+        assert!(dots.iter().all(|d| d.len() == 2 && d[0].y() == 0.0));
+
+        let mut closed = line(1.0);
+        closed.set_closed(true);
+        let dashes = DashPattern::new(vec![4.0, 4.0], 0.0, false).apply(&closed);
+        // The dash over the seam is one dash
+        assert_eq!(ends(&dashes), vec![(4.0, 4.0), (8.0, 8.0)]);
+        assert_eq!(
+            dashes[0].iter().map(|t| t.x()).collect::<Vec<_>>(),
+            vec![4.0, 0.0, 4.0]
+        );
+        assert!(!dashes[0].closed());
+
+        // Too short patterns are stretched instead of looping forever
+        let dense = DashPattern::new(vec![0.0, 1e-7], 0.0, false).apply(&line(1.0));
+        assert_eq!(dense.len(), 11);
+
+        let scaled = DashPattern::new(vec![2.0, 2.0], 0.0, true).apply(&line(0.5));
+        assert_eq!(
+            ends(&scaled),
+            vec![(0.0, 1.0), (2.0, 3.0), (4.0, 5.0), (6.0, 7.0), (8.0, 9.0)]
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use crate::geometry::Distance;
+
 #[derive(Debug)]
 pub struct DeduplicateIterator<I>
 where
@@ -37,3 +39,85 @@ where
         result
     }
 }
+
+/// Drops items closer than `min_distance` to the previous kept item,
+/// with a tiny distance it's a `DeduplicateIterator` tolerant to noise
+#[derive(Debug)]
+pub struct MinDistanceIterator<I>
+where
+    I: Iterator,
+    I::Item: Clone + Distance<Output = f32>,
+{
+    iterator: I,
+    min_distance: f32,
+    previous: Option<I::Item>,
+}
+
+impl<I> MinDistanceIterator<I>
+where
+    I: Iterator,
+    I::Item: Clone + Distance<Output = f32>,
+{
+    pub fn new(iterator: I, min_distance: f32) -> Self {
+        Self {
+            iterator,
+            min_distance,
+            previous: None,
+        }
+    }
+}
+
+impl<I> Iterator for MinDistanceIterator<I>
+where
+    I: Iterator,
+    I::Item: Clone + Distance<Output = f32>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let item = self.iterator.next()?;
+            let far = match &self.previous {
+                Some(previous) => previous.clone().distance(item.clone()) >= self.min_distance,
+                None => true,
+            };
+            if far {
+                self.previous = Some(item.clone());
+                return Some(item);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DeduplicateIterator, MinDistanceIterator};
+    use crate::geometry::Vector;
+
+    #[test]
+    fn deduplicate() {
+        let data = vec![1, 1, 2, 2, 2, 3, 1];
+        let result: Vec<_> = DeduplicateIterator::new(data.into_iter()).collect();
+        assert_eq!(result, vec![1, 2, 3, 1]);
+    }
+
+    #[test]
+    fn min_distance() {
+        let data = vec![
+            Vector::new(0.0, 0.0),
+            Vector::new(0.0, 1e-7),
+            Vector::new(1.0, 0.0),
+            Vector::new(1.5, 0.0),
+            Vector::new(2.1, 0.0),
+        ];
+        let result: Vec<_> = MinDistanceIterator::new(data.into_iter(), 1.0).collect();
+        assert_eq!(
+            result,
+            vec![
+                Vector::new(0.0, 0.0),
+                Vector::new(1.0, 0.0),
+                Vector::new(2.1, 0.0)
+            ]
+        );
+    }
+}
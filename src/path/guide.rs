@@ -0,0 +1,311 @@
+use crate::geometry::{Distance, Mix, Touch, Transform, Vector};
+use crate::touch_list::TouchList;
+use wasm_bindgen::prelude::*;
+
+#[derive(Clone, Debug, PartialEq)]
+enum GuideShape {
+    /// Infinite line through two points
+    Ruler(Vector, Vector),
+    /// Maps the unit circle to the ellipse
+    Ellipse(Transform),
+    /// Scaled copies of the ellipse, the stroke follows the one through its first touch
+    ConcentricEllipses(Transform),
+    /// Lines through vanishing points, the stroke follows the line through its first touch
+    /// and the vanishing point in the direction of the stroke
+    Perspective(Vec<Vector>),
+}
+
+/// Distance from the first touch, in screen pixels, after which the stroke direction
+/// is trusted to choose the perspective line, so the line doesn't switch mid-stroke
+const LOCK_DISTANCE: f32 = 8.0;
+
+/// Constrains a path drawn by hand to a shape
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Guide {
+    shape: GuideShape,
+    /// 0 keeps the path as is, 1 moves touches onto the shape
+    strength: f32,
+}
+
+fn ellipse_transform(x: f32, y: f32, rx: f32, ry: f32, angle: f32) -> Transform {
+    Transform::translate(Vector::new(x, y)) * Transform::rotate(angle) * Transform::scale(rx, ry)
+}
+
+#[wasm_bindgen]
+impl Guide {
+    pub fn ruler(x1: f32, y1: f32, x2: f32, y2: f32, strength: f32) -> Guide {
+        Guide::new(
+            GuideShape::Ruler(Vector::new(x1, y1), Vector::new(x2, y2)),
+            strength,
+        )
+    }
+
+    /// @param angle rotation of the ellipse in radians
+    pub fn ellipse(x: f32, y: f32, rx: f32, ry: f32, angle: f32, strength: f32) -> Guide {
+        Guide::new(
+            GuideShape::Ellipse(ellipse_transform(x, y, rx, ry, angle)),
+            strength,
+        )
+    }
+
+    /// Ellipses centered at (x, y) with the same proportions, `ratio` is `ry / rx`
+    /// @param angle rotation of ellipses in radians
+    pub fn concentric_ellipses(x: f32, y: f32, ratio: f32, angle: f32, strength: f32) -> Guide {
+        Guide::new(
+            GuideShape::ConcentricEllipses(ellipse_transform(x, y, 1.0, ratio, angle)),
+            strength,
+        )
+    }
+
+    /// One, two or three point perspective
+    /// @param points is an array [x1, y1, x2, y2, ...] of vanishing points
+    pub fn perspective(points: Vec<f32>, strength: f32) -> Guide {
+        assert_eq!(points.len() % 2, 0);
+        assert!((1..=3).contains(&(points.len() / 2)));
+        Guide::new(
+            GuideShape::Perspective(points.chunks(2).map(|p| Vector::new(p[0], p[1])).collect()),
+            strength,
+        )
+    }
+
+    pub fn apply(&self, path: &TouchList) -> TouchList {
+        let mut result = path.clone();
+        result.clear();
+        result.extend(GuideIterator::new(
+            path.iter().cloned(),
+            self.clone(),
+            path.pixel_size(),
+        ));
+        result
+    }
+}
+
+impl Guide {
+    fn new(shape: GuideShape, strength: f32) -> Guide {
+        Guide {
+            shape,
+            strength: strength.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Point of the shape for `point` of the stroke starting at `first`
+    pub fn snap(&self, first: Vector, point: Vector) -> Vector {
+        let snapped = match &self.shape {
+            GuideShape::Ruler(a, b) => project_on_line(*a, *b - *a, point),
+            GuideShape::Ellipse(transform) => project_on_ellipse(transform, 1.0, point),
+            GuideShape::ConcentricEllipses(transform) => match transform.invert() {
+                Some(inverse) => {
+                    project_on_ellipse(transform, inverse.apply_point(first).length(), point)
+                }
+                None => point,
+            },
+            GuideShape::Perspective(points) => match vanishing_point(points, first, point) {
+                Some(v) => project_on_line(first, v - first, point),
+                None => point,
+            },
+        };
+        point.mix(snapped, self.strength)
+    }
+
+    /// The guide with choices made for the stroke starting at `first` and going through
+    /// `point` fixed, the perspective guide keeps only the chosen vanishing point
+    pub fn locked(&self, first: Vector, point: Vector) -> Guide {
+        match &self.shape {
+            GuideShape::Perspective(points) => match vanishing_point(points, first, point) {
+                Some(v) => Guide::new(GuideShape::Perspective(vec![v]), self.strength),
+                None => self.clone(),
+            },
+            _ => self.clone(),
+        }
+    }
+}
+
+/// Vanishing point best aligned with the stroke direction, either way
+fn vanishing_point(points: &[Vector], first: Vector, point: Vector) -> Option<Vector> {
+    let direction = (point - first).normalize();
+    points.iter().copied().max_by(|a, b| {
+        let alignment = |v: Vector| (v - first).normalize().dot(direction).abs();
+        alignment(*a).total_cmp(&alignment(*b))
+    })
+}
+
+fn project_on_line(origin: Vector, direction: Vector, point: Vector) -> Vector {
+    let length = direction.dot(direction);
+    if length <= f32::EPSILON {
+        return origin;
+    }
+    origin + direction * ((point - origin).dot(direction) / length)
+}
+
+/// Radial projection on the ellipse with unit circle mapped by `transform`
+/// and scaled by `scale` around its center
+fn project_on_ellipse(transform: &Transform, scale: f32, point: Vector) -> Vector {
+    match transform.invert() {
+        Some(inverse) => {
+            let local = inverse.apply_point(point);
+            if local.length() <= f32::EPSILON {
+                point
+            } else {
+                transform.apply_point(local.normalize() * scale)
+            }
+        }
+        None => point,
+    }
+}
+
+/// Moves touches towards the guide, should go before interpolation.
+/// The guide is locked once the stroke moves `LOCK_DISTANCE` away from its first touch.
+pub struct GuideIterator<I>
+where
+    I: Iterator<Item = Touch>,
+{
+    iterator: I,
+    guide: Guide,
+    first: Option<Vector>,
+    locked: bool,
+    lock_distance: f32,
+}
+
+impl<I> GuideIterator<I>
+where
+    I: Iterator<Item = Touch>,
+{
+    /// @param pixel_size is the size of a screen pixel in path units
+    pub fn new(iterator: I, guide: Guide, pixel_size: f32) -> Self {
+        Self {
+            iterator,
+            guide,
+            first: None,
+            locked: false,
+            lock_distance: LOCK_DISTANCE * pixel_size,
+        }
+    }
+
+    /// Continues a stroke which started at `first`, `guide` should be the one
+    /// returned by `into_guide` of the previous part
+    pub fn continued(iterator: I, guide: Guide, first: Vector, pixel_size: f32) -> Self {
+        Self {
+            first: Some(first),
+            ..Self::new(iterator, guide, pixel_size)
+        }
+    }
+
+    /// The guide, locked if the stroke went far enough
+    pub fn into_guide(self) -> Guide {
+        self.guide
+    }
+}
+
+impl<I> Iterator for GuideIterator<I>
+where
+    I: Iterator<Item = Touch>,
+{
+    type Item = Touch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let touch = self.iterator.next()?;
+        let first = *self.first.get_or_insert(touch.point);
+        if !self.locked && first.distance(touch.point) >= self.lock_distance {
+            self.guide = self.guide.locked(first, touch.point);
+            self.locked = true;
+        }
+        Some(Touch {
+            point: self.guide.snap(first, touch.point),
+            ..touch
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Guide, GuideIterator};
+    use crate::geometry::{Distance, Touch, Vector};
+    use crate::touch_list::TouchList;
+    use num_traits::Zero;
+
+    fn assert_close(a: Vector, b: Vector) {
+        assert!(a.distance(b) < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn ruler() {
+        let guide = Guide::ruler(0.0, 0.0, 10.0, 10.0, 1.0);
+        let mut path = TouchList::new(1.0);
+        path.push(4.0, 6.0, 0.5);
+        path.push(0.0, 2.0, 0.7);
+        let guided = guide.apply(&path);
+        assert_close(guided[0].point, Vector::new(5.0, 5.0));
+        assert_close(guided[1].point, Vector::new(1.0, 1.0));
+        assert_eq!(guided[1].pressure, 0.7);
+
+        let half = Guide::ruler(0.0, 0.0, 10.0, 10.0, 0.5);
+        assert_close(
+            half.snap(Vector::zero(), Vector::new(4.0, 6.0)),
+            Vector::new(4.5, 5.5),
+        );
+    }
+
+    #[test]
+    fn ellipses() {
+        let guide = Guide::ellipse(10.0, 0.0, 4.0, 2.0, 0.0, 1.0);
+        assert_close(
+            guide.snap(Vector::zero(), Vector::new(20.0, 0.0)),
+            Vector::new(14.0, 0.0),
+        );
+        assert_close(
+            guide.snap(Vector::zero(), Vector::new(10.0, -1.0)),
+            Vector::new(10.0, -2.0),
+        );
+
+        let guide = Guide::concentric_ellipses(0.0, 0.0, 0.5, 0.0, 1.0);
+        let first = Vector::new(6.0, 0.0);
+        assert_close(
+            guide.snap(first, Vector::new(0.0, 5.0)),
+            Vector::new(0.0, 3.0),
+        );
+    }
+
+    #[test]
+    fn perspective() {
+        let guide = Guide::perspective(vec![-100.0, 0.0, 100.0, 0.0, 0.0, -1000.0], 1.0);
+        let first = Vector::new(0.0, 10.0);
+        // Mostly horizontal stroke follows the horizon vanishing point line
+        let snapped = guide.snap(first, Vector::new(50.0, 14.0));
+        assert!((snapped.y - (10.0 + 0.1 * snapped.x)).abs() < 1e-3);
+        // Mostly vertical stroke goes to the third vanishing point
+        let snapped = guide.snap(first, Vector::new(2.0, 50.0));
+        assert!(snapped.x.abs() < 1e-4);
+    }
+
+    #[test]
+    fn perspective_lock() {
+        let guide = Guide::perspective(vec![100.0, 0.0, 0.0, 100.0], 1.0);
+        // Starts towards the first vanishing point, then bends past the bisector
+        let mut path = TouchList::new(1.0);
+        path.push(0.0, 0.0, 0.5);
+        path.push(10.0, 1.0, 0.5);
+        path.push(20.0, 30.0, 0.5);
+        let guided = guide.apply(&path);
+        for touch in guided.iter() {
+            assert!(touch.y().abs() < 1e-4, "{:?}", touch);
+        }
+
+        // The lock is kept by continued parts
+        let mut start = TouchList::new(1.0);
+        start.push(0.0, 0.0, 0.5);
+        start.push(10.0, 1.0, 0.5);
+        let mut iterator = GuideIterator::new(start.iter().cloned(), guide, 1.0);
+        assert_eq!(iterator.by_ref().count(), 2);
+        let locked = iterator.into_guide();
+        let touch = GuideIterator::continued(
+            vec![Touch::new(20.0, 30.0, 0.5)].into_iter(),
+            locked,
+            Vector::zero(),
+            1.0,
+        )
+        .next()
+        .unwrap();
+        assert!(touch.y().abs() < 1e-4);
+    }
+}
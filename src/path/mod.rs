@@ -1,15 +1,29 @@
+mod catmull_rom;
+mod dash;
 mod deduplicate;
+mod guide;
+mod predict;
 mod segments;
+// Not re-exported, `SimulatedPressure` is only used from JavaScript
+mod simulated_pressure;
 mod smooth;
 mod split;
+mod with_dynamics;
+mod with_index;
 mod with_neighbours;
 mod with_normal;
 mod with_offset;
 
+pub use catmull_rom::*;
+pub use dash::*;
 pub use deduplicate::*;
+pub use guide::*;
+pub use predict::*;
 pub use segments::*;
 pub use smooth::*;
 pub use split::*;
+pub use with_dynamics::*;
+pub use with_index::*;
 pub use with_neighbours::*;
 pub use with_normal::*;
 pub use with_offset::*;
@@ -0,0 +1,156 @@
+use crate::geometry::{Touch, Vector};
+use crate::touch_list::TouchList;
+
+/// Extrapolates pointer motion from the last input touches to hide input latency.
+///
+/// Velocity and acceleration are measured from the times of the touches, see
+/// `TouchList::push_at`, and predicted touches are as far apart in time as the last two.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Predictor {
+    /// How far ahead touches are predicted, in milliseconds
+    pub horizon: f32,
+    /// Prediction stops when confidence drops below this value, in range [0, 1]
+    pub min_confidence: f32,
+}
+
+/// Slower motion, in screen pixels per millisecond, is treated as jitter and isn't predicted
+const MIN_SPEED: f32 = 0.05;
+
+impl Predictor {
+    pub fn new(horizon: f32, min_confidence: f32) -> Predictor {
+        Predictor {
+            horizon,
+            min_confidence,
+        }
+    }
+
+    /// Confidence of a prediction `interval` milliseconds ahead, 1 for uniform motion,
+    /// it drops as the change of velocity over the interval grows relative to velocity
+    pub fn confidence(
+        velocity: Vector,
+        acceleration: Vector,
+        interval: f32,
+        pixel_size: f32,
+    ) -> f32 {
+        let speed = velocity.length();
+        if speed < MIN_SPEED * pixel_size {
+            0.0
+        } else {
+            (1.0 - acceleration.length() * interval / speed).clamp(0.0, 1.0)
+        }
+    }
+
+    /// Touches following the path, confidence of every next touch is multiplied
+    /// by the confidence of one interval. Paths without times aren't predicted.
+    pub fn predict(&self, path: &TouchList) -> Vec<Touch> {
+        let (p0, p1, p2) = match path.as_slice() {
+            [.., p0, p1, p2] => (*p0, *p1, *p2),
+            _ => return Vec::new(),
+        };
+        let (t0, t1, t2) = match path.times() {
+            Some([.., t0, t1, t2]) => (*t0, *t1, *t2),
+            _ => return Vec::new(),
+        };
+        let (dt1, dt2) = ((t1 - t0) as f32, (t2 - t1) as f32);
+        if dt1 <= 0.0 || dt2 <= 0.0 {
+            return Vec::new();
+        }
+        let velocity = (p2.point - p1.point) * (1.0 / dt2);
+        let previous_velocity = (p1.point - p0.point) * (1.0 / dt1);
+        let acceleration = (velocity - previous_velocity) * (2.0 / (dt1 + dt2));
+        let pressure_velocity = (p2.pressure - p1.pressure) / dt2;
+        let step = Predictor::confidence(velocity, acceleration, dt2, path.pixel_size());
+
+        let mut result = Vec::new();
+        let mut confidence = step;
+        let mut t = dt2;
+        while t <= self.horizon {
+            if confidence < self.min_confidence || confidence == 0.0 {
+                break;
+            }
+            result.push(Touch {
+                point: p2.point + velocity * t + acceleration * (t * t * 0.5),
+                pressure: (p2.pressure + pressure_velocity * t).clamp(0.0, 1.0),
+                tilt: p2.tilt,
+            });
+            confidence *= step;
+            t += dt2;
+        }
+        result
+    }
+}
+
+impl Default for Predictor {
+    /// No prediction
+    fn default() -> Self {
+        Predictor::new(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Predictor;
+    use crate::touch_list::TouchList;
+
+    fn path(touches: &[(f32, f32, f64)]) -> TouchList {
+        let mut path = TouchList::new(1.0);
+        for &(x, y, time) in touches {
+            path.push_at(x, y, 0.5, 0.0, time);
+        }
+        path
+    }
+
+    #[test]
+    fn uniform_motion() {
+        let mut touches = TouchList::new(1.0);
+        for i in 0..4 {
+            let i = i as f32;
+            touches.push_at(i * 4.0, i * 2.0, 0.2 + i * 0.1, 0.0, i as f64 * 8.0);
+        }
+        // Three events of 8 milliseconds
+        let predicted = Predictor::new(30.0, 0.5).predict(&touches);
+        assert_eq!(predicted.len(), 3);
+        for (i, touch) in predicted.iter().enumerate() {
+            let i = (i + 4) as f32;
+            assert!((touch.x() - i * 4.0).abs() < 1e-4);
+            assert!((touch.y() - i * 2.0).abs() < 1e-4);
+            assert!((touch.pressure - (0.2 + i * 0.1)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn timestamps() {
+        // The same motion sampled with uneven intervals
+        let touches = path(&[(0.0, 0.0, 0.0), (2.0, 0.0, 4.0), (10.0, 0.0, 20.0)]);
+        let predicted = Predictor::new(16.0, 0.5).predict(&touches);
+        assert_eq!(predicted.len(), 1);
+        assert!((predicted[0].x() - 18.0).abs() < 1e-4);
+
+        // Events without times or at the same time
+        let mut untimed = TouchList::new(1.0);
+        for i in 0..3 {
+            untimed.push(i as f32 * 4.0, 0.0, 0.5);
+        }
+        assert!(Predictor::new(16.0, 0.5).predict(&untimed).is_empty());
+        let touches = path(&[(0.0, 0.0, 0.0), (2.0, 0.0, 4.0), (10.0, 0.0, 4.0)]);
+        assert!(Predictor::new(16.0, 0.5).predict(&touches).is_empty());
+    }
+
+    #[test]
+    fn confidence() {
+        let predictor = Predictor::new(100.0, 0.5);
+        // Decelerating, velocity drops by a quarter every 10 milliseconds
+        let touches = path(&[(0.0, 0.0, 0.0), (10.0, 0.0, 10.0), (18.0, 0.0, 20.0)]);
+        // 0.75, 0.5625, 0.42
+        assert_eq!(predictor.predict(&touches).len(), 2);
+
+        // Sharp turn
+        let touches = path(&[(0.0, 0.0, 0.0), (10.0, 0.0, 10.0), (10.0, 10.0, 20.0)]);
+        assert!(predictor.predict(&touches).is_empty());
+
+        // Jitter of a resting pen
+        let touches = path(&[(0.0, 0.0, 0.0), (0.1, 0.0, 10.0), (0.2, 0.0, 20.0)]);
+        assert!(predictor.predict(&touches).is_empty());
+        assert!(Predictor::default().predict(&touches).is_empty());
+    }
+}
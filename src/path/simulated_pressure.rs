@@ -0,0 +1,194 @@
+use crate::geometry::{easing, Distance, Touch};
+use crate::touch_list::TouchList;
+use std::vec::IntoIter;
+use wasm_bindgen::prelude::*;
+
+/// Replaces pressure of mouse and touch input, which comes constant, with pressure
+/// falling with pointer speed and tapered at stroke ends
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SimulatedPressure {
+    /// Length of the taper at the stroke start, in screen pixels
+    ease_in: f32,
+    /// Length of the taper at the stroke end, in screen pixels
+    ease_out: f32,
+    /// Speed in screen pixels per event at which pressure halves, 0 ignores speed
+    half_speed: f32,
+}
+
+#[wasm_bindgen]
+impl SimulatedPressure {
+    #[wasm_bindgen(constructor)]
+    pub fn new(ease_in: f32, ease_out: f32, half_speed: f32) -> SimulatedPressure {
+        SimulatedPressure {
+            ease_in: ease_in.max(0.0),
+            ease_out: ease_out.max(0.0),
+            half_speed: half_speed.max(0.0),
+        }
+    }
+
+    pub fn apply(&self, path: &TouchList) -> TouchList {
+        let mut result = path.clone();
+        result.clear();
+        result.extend(SimulatedPressureIterator::new(
+            path.iter().cloned(),
+            *self,
+            path.pixel_size(),
+        ));
+        result
+    }
+}
+
+impl SimulatedPressure {
+    /// Pressure for pointer moving at `speed` screen pixels per event
+    pub fn speed_pressure(&self, speed: f32) -> f32 {
+        if self.half_speed <= 0.0 {
+            1.0
+        } else {
+            1.0 / (1.0 + speed / self.half_speed)
+        }
+    }
+
+    /// Taper at `offset` screen pixels from the start of a `length` pixels long stroke,
+    /// tapers of short strokes are shortened to fit
+    pub fn taper(&self, offset: f32, length: f32) -> f32 {
+        let ease = self.ease_in + self.ease_out;
+        let scale = if ease > length { length / ease } else { 1.0 };
+        let factor = |distance: f32, ease: f32| {
+            if ease <= 0.0 {
+                1.0
+            } else {
+                easing(distance / (ease * scale))
+            }
+        };
+        factor(offset, self.ease_in) * factor(length - offset, self.ease_out)
+    }
+}
+
+/// Synthesises touch pressure, see `SimulatedPressure`.
+///
+/// The taper at the stroke end needs the whole stroke, so the input is read on the first call.
+/// Strokes without length, like taps, aren't tapered.
+pub struct SimulatedPressureIterator<I>
+where
+    I: Iterator<Item = Touch>,
+{
+    iterator: Option<I>,
+    settings: SimulatedPressure,
+    pixel_size: f32,
+    touches: IntoIter<Touch>,
+}
+
+impl<I> SimulatedPressureIterator<I>
+where
+    I: Iterator<Item = Touch>,
+{
+    /// @param pixel_size is the size of a screen pixel in path units
+    pub fn new(iterator: I, settings: SimulatedPressure, pixel_size: f32) -> Self {
+        Self {
+            iterator: Some(iterator),
+            settings,
+            pixel_size,
+            touches: Vec::new().into_iter(),
+        }
+    }
+
+    fn simulate(&self, mut touches: Vec<Touch>) -> Vec<Touch> {
+        let pixel_size = if self.pixel_size > 0.0 {
+            self.pixel_size
+        } else {
+            1.0
+        };
+        let steps: Vec<f32> = touches
+            .windows(2)
+            .map(|w| w[0].distance(w[1]) / pixel_size)
+            .collect();
+        let length: f32 = steps.iter().sum();
+        let mut offset = 0.0;
+        for (i, touch) in touches.iter_mut().enumerate() {
+            // Speed around the touch, averaged over adjacent steps to reduce jitter
+            let before = i.checked_sub(1).and_then(|i| steps.get(i));
+            let after = steps.get(i);
+            let speed = match (before, after) {
+                (Some(a), Some(b)) => (a + b) * 0.5,
+                (Some(s), None) | (None, Some(s)) => *s,
+                (None, None) => 0.0,
+            };
+            let taper = if length > 0.0 {
+                self.settings.taper(offset, length)
+            } else {
+                1.0
+            };
+            touch.pressure = self.settings.speed_pressure(speed) * taper;
+            offset += after.copied().unwrap_or(0.0);
+        }
+        touches
+    }
+}
+
+impl<I> Iterator for SimulatedPressureIterator<I>
+where
+    I: Iterator<Item = Touch>,
+{
+    type Item = Touch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(iterator) = self.iterator.take() {
+            self.touches = self.simulate(iterator.collect()).into_iter();
+        }
+        self.touches.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SimulatedPressure;
+    use crate::touch_list::TouchList;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn taper() {
+        let mut path = TouchList::new(0.5);
+        for i in 0..=10 {
+            path.push(i as f32, 0.0, 0.5);
+        }
+        // 20 screen pixels long with 2 pixels per event
+        let simulated = SimulatedPressure::new(4.0, 8.0, 0.0).apply(&path);
+        assert_eq!(simulated.len(), path.len());
+        assert_close(simulated[0].pressure, 0.0);
+        assert_close(simulated[1].pressure, 0.5);
+        assert_close(simulated[2].pressure, 1.0);
+        assert_close(simulated[6].pressure, 1.0);
+        assert_close(simulated[8].pressure, 0.5);
+        assert_close(simulated[10].pressure, 0.0);
+        assert_eq!(simulated[3].point, path[3].point);
+
+        // Tapers are shortened for short strokes
+        let settings = SimulatedPressure::new(10.0, 10.0, 0.0);
+        assert_close(settings.taper(5.0, 10.0), 1.0);
+        assert_close(settings.taper(2.5, 10.0), 0.5);
+    }
+
+    #[test]
+    fn speed() {
+        let mut path = TouchList::new(1.0);
+        for x in [0.0, 2.0, 4.0, 10.0, 16.0] {
+            path.push(x, 0.0, 0.5);
+        }
+        let simulated = SimulatedPressure::new(0.0, 0.0, 2.0).apply(&path);
+        assert_close(simulated[1].pressure, 0.5);
+        assert_close(simulated[2].pressure, 1.0 / 3.0);
+        assert_close(simulated[3].pressure, 0.25);
+        assert_close(simulated[4].pressure, 0.25);
+
+        // Taps keep pressure of resting pointer
+        let mut tap = TouchList::new(1.0);
+        tap.push(1.0, 1.0, 0.5);
+        tap.push(1.0, 1.0, 0.5);
+        let simulated = SimulatedPressure::new(4.0, 4.0, 2.0).apply(&tap);
+        assert_close(simulated[0].pressure, 1.0);
+    }
+}
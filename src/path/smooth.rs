@@ -20,6 +20,13 @@ where
             iterator: WithNeighboursIterator::new(iterator),
         }
     }
+
+    /// Smooths a closed path, the first and the last points are neighbours
+    pub fn closed(iterator: I) -> Self {
+        Self {
+            iterator: WithNeighboursIterator::closed(iterator),
+        }
+    }
 }
 
 impl<I> Iterator for SmoothIterator<I>
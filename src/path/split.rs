@@ -3,6 +3,41 @@ use crate::path::{SegmentsIterator, WithNeighbours, WithNeighboursIterator, With
 use num_traits::zero;
 use std::fmt::Debug;
 
+/// Repeats the first item at the end of closed paths, so they get the closing segment
+#[derive(Debug)]
+struct ClosingIterator<I>
+where
+    I: Iterator,
+{
+    iterator: I,
+    first: Option<I::Item>,
+    count: usize,
+    closed: bool,
+}
+
+impl<I> Iterator for ClosingIterator<I>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.iterator.next() {
+            Some(item) => {
+                if self.count == 0 && self.closed {
+                    self.first = Some(item.clone());
+                }
+                self.count += 1;
+                Some(item)
+            }
+            // A single point has nothing to close
+            None if self.count > 1 => self.first.take(),
+            None => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct SplitIterator<I>
 where
@@ -10,10 +45,12 @@ where
     I::Item: Mix + Distance + Clone + Debug,
     <I::Item as Distance>::Output: Debug,
 {
-    iterator: WithNeighboursIterator<WithOffsetIterator<I>>,
-    segment: Option<<WithNeighboursIterator<WithOffsetIterator<I>> as Iterator>::Item>,
+    iterator: WithNeighboursIterator<WithOffsetIterator<ClosingIterator<I>>>,
+    segment:
+        Option<<WithNeighboursIterator<WithOffsetIterator<ClosingIterator<I>>> as Iterator>::Item>,
     offset: <I::Item as Distance>::Output,
     step: <I::Item as Distance>::Output,
+    closed: bool,
 }
 
 impl<I> SplitIterator<I>
@@ -23,11 +60,27 @@ where
     <I::Item as Distance>::Output: Debug + Into<<I::Item as Mix>::Fraction>,
 {
     pub fn new(iterator: I, step: <I::Item as Distance>::Output) -> Self {
+        Self::with_closed(iterator, step, false)
+    }
+
+    /// Splits a closed path including the segment from the last point to the first one,
+    /// the first point isn't repeated at the end
+    pub fn closed(iterator: I, step: <I::Item as Distance>::Output) -> Self {
+        Self::with_closed(iterator, step, true)
+    }
+
+    fn with_closed(iterator: I, step: <I::Item as Distance>::Output, closed: bool) -> Self {
         Self {
-            iterator: WithNeighboursIterator::new(WithOffsetIterator::new(iterator)),
+            iterator: WithNeighboursIterator::new(WithOffsetIterator::new(ClosingIterator {
+                iterator,
+                first: None,
+                count: 0,
+                closed,
+            })),
             segment: None,
             step,
             offset: zero(),
+            closed,
         }
     }
 }
@@ -60,6 +113,10 @@ where
             (Some(p1), p2, _) => {
                 if p2.offset <= offset {
                     self.segment = None;
+                    // The end of a closed path is its first point
+                    if self.closed {
+                        return None;
+                    }
                     Some(p2.point)
                 } else {
                     let r = p1.point.mix(
@@ -125,4 +182,23 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn closed() {
+        let points: Vec<_> = SplitIterator::closed(
+            vec![
+                Vector::new(0.0, 0.0),
+                Vector::new(1.0, 0.0),
+                Vector::new(1.0, 1.0),
+            ]
+            .into_iter(),
+            0.5,
+        )
+        .collect();
+
+        assert_eq!(points.len(), 7);
+        assert_eq!(points[4], Vector::new(1.0, 1.0));
+        assert!(points[6].x > 0.0 && points[6].x < 1.0);
+        assert_eq!(points[6].x, points[6].y);
+    }
 }
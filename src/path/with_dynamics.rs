@@ -0,0 +1,233 @@
+use crate::dynamics::{Dynamics, SensorValues};
+use crate::geometry::{Distance, Mix, Noise, Normal, Touch, Vector};
+use crate::path::{WithNeighbours, WithNeighboursIterator};
+use crate::random::Random;
+
+/// Point with factors of tool parameters, in the order of `Dynamics::parameters`
+#[derive(Debug)]
+pub struct WithDynamics<T> {
+    pub point: T,
+    pub factors: Vec<f32>,
+}
+
+impl<T: Clone> Clone for WithDynamics<T> {
+    fn clone(&self) -> Self {
+        Self {
+            point: self.point.clone(),
+            factors: self.factors.clone(),
+        }
+    }
+}
+
+impl<T> WithDynamics<T> {
+    /// Factor of the parameter with index from `Dynamics::index`, 1 if it isn't modulated
+    pub fn factor(&self, index: Option<usize>) -> f32 {
+        index
+            .and_then(|i| self.factors.get(i).copied())
+            .unwrap_or(1.0)
+    }
+}
+
+impl<T: Distance> Distance for WithDynamics<T> {
+    type Output = T::Output;
+
+    fn distance(self, other: Self) -> Self::Output {
+        self.point.distance(other.point)
+    }
+}
+
+impl<T> Mix for WithDynamics<T>
+where
+    T: Mix<Fraction = f32>,
+{
+    type Fraction = T::Fraction;
+
+    fn mix(self, other: Self, t: Self::Fraction) -> Self {
+        Self {
+            factors: self
+                .factors
+                .iter()
+                .zip(other.factors.iter())
+                .map(|(a, b)| a.mix(*b, t))
+                .collect(),
+            point: self.point.mix(other.point, t),
+        }
+    }
+}
+
+impl<T: Normal> Normal for WithDynamics<T> {
+    fn normal(p1: Self, p2: Self) -> Vector {
+        T::normal(p1.point, p2.point)
+    }
+}
+
+/// Sensor state of a stroke before the touch with `index`, so dynamics of a part
+/// of the stroke are evaluated without going through the touches before it
+#[derive(Clone, Debug, PartialEq)]
+pub struct DynamicsState {
+    seed: i32,
+    index: usize,
+    random: Random,
+    /// Length of the stroke up to the previous touch
+    offset: f32,
+}
+
+impl DynamicsState {
+    /// State at the stroke start
+    pub fn new(seed: i32) -> DynamicsState {
+        DynamicsState {
+            seed,
+            index: 0,
+            random: Random::new(seed),
+            offset: 0.0,
+        }
+    }
+
+    pub fn seed(&self) -> i32 {
+        self.seed
+    }
+
+    /// Moves the state to the touch with `index` of `path`, which must be the path
+    /// the state was advanced along before. Only touches after the current one are read,
+    /// a state past `index` starts over from the stroke start.
+    pub fn advance(&mut self, path: &[Touch], index: usize) {
+        if index < self.index {
+            *self = DynamicsState::new(self.seed);
+        }
+        for i in self.index..index.min(path.len()) {
+            if i > 0 {
+                self.offset += path[i - 1].distance(path[i]);
+            }
+            self.random.next_f32();
+        }
+        self.index = index;
+    }
+
+    /// State at the start of a separate path continuing the stroke `offset` along it,
+    /// like a dash cut out of the stroke path
+    pub fn rebased(&self, offset: f32) -> DynamicsState {
+        DynamicsState {
+            index: 0,
+            offset,
+            ..self.clone()
+        }
+    }
+}
+
+/// Evaluates dynamics for raw input touches, should go before interpolation
+/// so sensors see input events, not generated points
+pub struct WithDynamicsIterator<'a, I>
+where
+    I: Iterator<Item = Touch>,
+{
+    iterator: WithNeighboursIterator<I>,
+    dynamics: &'a Dynamics,
+    pixel_size: f32,
+    state: DynamicsState,
+    noise: Noise,
+}
+
+impl<'a, I> WithDynamicsIterator<'a, I>
+where
+    I: Iterator<Item = Touch>,
+{
+    /// @param pixel_size is the size of a screen pixel in path units, see `TouchList::pixel_size`
+    pub fn new(iterator: I, dynamics: &'a Dynamics, pixel_size: f32, seed: i32) -> Self {
+        Self::continued(
+            iterator,
+            None,
+            dynamics,
+            pixel_size,
+            DynamicsState::new(seed),
+        )
+    }
+
+    /// Continues a stroke from `state`, `iterator` starts with the touch the state
+    /// was advanced to and `previous` is the touch before it
+    pub fn continued(
+        iterator: I,
+        previous: Option<Touch>,
+        dynamics: &'a Dynamics,
+        pixel_size: f32,
+        state: DynamicsState,
+    ) -> Self {
+        Self {
+            iterator: WithNeighboursIterator::continued(previous, iterator),
+            dynamics,
+            pixel_size,
+            // Forked, so the noise doesn't repeat values of the random sensor
+            noise: Noise::with_random(Random::new(state.seed).fork()),
+            state,
+        }
+    }
+
+    fn sensors(&mut self, touches: &WithNeighbours<Touch>) -> SensorValues {
+        let (previous, current, next) = touches;
+        if let Some(previous) = previous {
+            self.state.offset += previous.distance(*current);
+        }
+        self.state.index += 1;
+        // Movement around the touch, central difference where both neighbours exist
+        let (from, to, steps) = match (previous, next) {
+            (Some(p), Some(n)) => (*p, *n, 2.0),
+            (Some(p), None) => (*p, *current, 1.0),
+            (None, Some(n)) => (*current, *n, 1.0),
+            (None, None) => (*current, *current, 1.0),
+        };
+        let movement = to.point - from.point;
+        SensorValues {
+            pressure: current.pressure,
+            velocity: movement.length() / steps / self.pixel_size,
+            tilt: current.tilt,
+            direction: movement.y.atan2(movement.x),
+            offset: self.state.offset,
+            random: self.state.random.next_f32(),
+        }
+    }
+}
+
+impl<'a, I> Iterator for WithDynamicsIterator<'a, I>
+where
+    I: Iterator<Item = Touch>,
+{
+    type Item = WithDynamics<Touch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let touches = self.iterator.next()?;
+        let sensors = self.sensors(&touches);
+        Some(WithDynamics {
+            point: touches.1,
+            factors: self.dynamics.factors(&sensors, &self.noise),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WithDynamicsIterator;
+    use crate::dynamics::{Dynamics, Sensor};
+    use crate::geometry::{PressureCurve, Touch};
+
+    #[test]
+    fn sensors() {
+        let mut dynamics = Dynamics::new();
+        let linear = PressureCurve::linear();
+        dynamics.add(Sensor::Pressure, "size".into(), 0.0, 1.0, 0.0, &linear);
+        dynamics.add(Sensor::Fade, "opacity".into(), 0.0, 1.0, 20.0, &linear);
+        dynamics.add(Sensor::Velocity, "spacing".into(), 0.0, 1.0, 0.0, &linear);
+        dynamics.add(Sensor::Random, "jitter".into(), 0.0, 1.0, 0.0, &linear);
+
+        let touches = (0..4).map(|i| Touch::new(i as f32 * 10.0, 0.0, i as f32 * 0.25));
+        let points: Vec<_> = WithDynamicsIterator::new(touches, &dynamics, 0.5, 1).collect();
+        let factors: Vec<_> = points.iter().map(|p| p.factors.clone()).collect();
+        assert_eq!(factors[0][..3], [0.0, 1.0, 0.4]);
+        assert_eq!(factors[1][..3], [0.25, 0.5, 0.4]);
+        assert_eq!(factors[3][..3], [0.75, 0.0, 0.4]);
+
+        let replay: Vec<_> =
+            WithDynamicsIterator::new(points.iter().map(|p| p.point), &dynamics, 0.5, 1).collect();
+        assert_eq!(replay[2].factors, factors[2]);
+        assert_ne!(factors[0][3], factors[1][3]);
+        assert_eq!(points[0].factor(dynamics.index("thickness")), 1.0);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::geometry::{Distance, Mix, Normal, Vector};
+
+/// Point with position in the input path, interpolated points get fractional indices,
+/// so points generated between input points `i` and `i + 1` have index in range [i, i + 1]
+#[derive(Debug)]
+pub struct WithIndex<T> {
+    pub point: T,
+    pub index: f32,
+}
+
+impl<T: Clone> Clone for WithIndex<T> {
+    fn clone(&self) -> Self {
+        Self {
+            point: self.point.clone(),
+            index: self.index,
+        }
+    }
+}
+
+impl<T: Copy> Copy for WithIndex<T> {}
+
+impl<T: Distance> Distance for WithIndex<T> {
+    type Output = T::Output;
+
+    fn distance(self, other: Self) -> Self::Output {
+        self.point.distance(other.point)
+    }
+}
+
+impl<T> Mix for WithIndex<T>
+where
+    T: Mix<Fraction = f32>,
+{
+    type Fraction = T::Fraction;
+
+    fn mix(self, other: Self, t: Self::Fraction) -> Self {
+        Self {
+            point: self.point.mix(other.point, t),
+            index: self.index.mix(other.index, t),
+        }
+    }
+}
+
+impl<T: Normal> Normal for WithIndex<T> {
+    fn normal(p1: Self, p2: Self) -> Vector {
+        T::normal(p1.point, p2.point)
+    }
+}
+
+pub struct WithIndexIterator<I>
+where
+    I: Iterator,
+{
+    iterator: I,
+    index: usize,
+}
+
+impl<I> WithIndexIterator<I>
+where
+    I: Iterator,
+{
+    /// @param start is the index of the first point
+    pub fn new(iterator: I, start: usize) -> Self {
+        Self {
+            iterator,
+            index: start,
+        }
+    }
+}
+
+impl<I> Iterator for WithIndexIterator<I>
+where
+    I: Iterator,
+{
+    type Item = WithIndex<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let point = self.iterator.next()?;
+        self.index += 1;
+        Some(WithIndex {
+            point,
+            index: (self.index - 1) as f32,
+        })
+    }
+}
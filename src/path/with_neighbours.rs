@@ -1,5 +1,9 @@
+use std::collections::VecDeque;
+
 pub type WithNeighbours<T> = (Option<T>, T, Option<T>);
 
+/// Yields items with their previous and next items, in closed paths the first
+/// and the last items are neighbours of each other
 #[derive(Debug)]
 pub struct WithNeighboursIterator<I>
 where
@@ -8,6 +12,10 @@ where
 {
     iterator: I,
     state: (Option<I::Item>, Option<I::Item>),
+    /// Items read ahead to find the last one of a closed path
+    buffer: VecDeque<I::Item>,
+    /// Next neighbour of the last item of a closed path
+    first: Option<I::Item>,
 }
 
 impl<I> WithNeighboursIterator<I>
@@ -15,10 +23,34 @@ where
     I: Iterator,
     I::Item: Clone,
 {
-    pub fn new(mut iterator: I) -> Self {
+    pub fn new(iterator: I) -> Self {
+        Self::continued(None, iterator)
+    }
+
+    /// Continues a path after `previous`, which becomes the previous neighbour of the first item
+    pub fn continued(previous: Option<I::Item>, mut iterator: I) -> Self {
         Self {
-            state: (None, iterator.next()),
+            state: (previous, iterator.next()),
             iterator,
+            buffer: VecDeque::new(),
+            first: None,
+        }
+    }
+
+    /// Wraps neighbours around, the whole input is read on creation.
+    /// A single item has no neighbours.
+    pub fn closed(mut iterator: I) -> Self {
+        let mut buffer: VecDeque<_> = iterator.by_ref().collect();
+        let current = buffer.pop_front();
+        let (previous, first) = match buffer.back() {
+            Some(last) => (Some(last.clone()), current.clone()),
+            None => (None, None),
+        };
+        Self {
+            state: (previous, current),
+            iterator,
+            buffer,
+            first,
         }
     }
 }
@@ -31,10 +63,15 @@ where
     type Item = WithNeighbours<I::Item>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let current = self.state.1.as_ref()?.clone();
-        let previous = self.state.0.clone();
-        let next = self.iterator.next();
-        self.state = (Some(current.clone()), next.clone());
+        let current = self.state.1.take()?;
+        let previous = self.state.0.take();
+        let next = match self.buffer.pop_front().or_else(|| self.iterator.next()) {
+            Some(next) => {
+                self.state = (Some(current.clone()), Some(next.clone()));
+                Some(next)
+            }
+            None => self.first.take(),
+        };
         Some((previous, current, next))
     }
 }
@@ -64,4 +101,21 @@ mod test {
         let result: Vec<_> = WithNeighboursIterator::new(data.into_iter()).collect();
         assert_eq!(result, vec![(None, 1.0, None)])
     }
+
+    #[test]
+    fn closed() {
+        let data: Vec<f32> = vec![1.0, 2.0, 3.0];
+        let result: Vec<_> = WithNeighboursIterator::closed(data.into_iter()).collect();
+        assert_eq!(
+            result,
+            vec![
+                (Some(3.0), 1.0, Some(2.0)),
+                (Some(1.0), 2.0, Some(3.0)),
+                (Some(2.0), 3.0, Some(1.0)),
+            ]
+        );
+
+        let single: Vec<_> = WithNeighboursIterator::closed(vec![1.0].into_iter()).collect();
+        assert_eq!(single, vec![(None, 1.0, None)])
+    }
 }
@@ -31,7 +31,7 @@ impl<T> WithNormal<T> {
 pub struct WithNormalIterator<I>
 where
     I: Iterator,
-    I::Item: Copy + Normal,
+    I::Item: Clone + Normal,
 {
     iterator: WithNeighboursIterator<I>,
 }
@@ -39,33 +39,46 @@ where
 impl<I> WithNormalIterator<I>
 where
     I: Iterator,
-    I::Item: Copy + Normal,
+    I::Item: Clone + Normal,
 {
     pub fn new(iterator: I) -> Self {
         Self {
             iterator: WithNeighboursIterator::new(iterator),
         }
     }
+
+    /// Normals of a closed path, the first and the last points are neighbours
+    pub fn closed(iterator: I) -> Self {
+        Self {
+            iterator: WithNeighboursIterator::closed(iterator),
+        }
+    }
 }
 
 impl<I> Iterator for WithNormalIterator<I>
 where
     I: Iterator,
-    I::Item: Copy + Normal,
+    I::Item: Clone + Normal,
 {
     type Item = WithNormal<I::Item>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let point = self.iterator.next()?;
-        Some(WithNormal {
-            point: point.1,
-            normal: match point {
-                (Some(p1), _, Some(p3)) => Normal::normal(p1, p3).normalize(),
-                (_, p2, Some(p3)) => Normal::normal(p2, p3).normalize(),
-                (Some(p1), p2, _) => Normal::normal(p1, p2).normalize(),
-                _ => Vector::zero(),
-            },
-        })
+        let (p1, p2, p3) = self.iterator.next()?;
+        // Neighbours at the same position give no direction, the next pair is tried then
+        let normals = [
+            (p1.clone(), p3.clone()),
+            (Some(p2.clone()), p3),
+            (p1, Some(p2.clone())),
+        ];
+        let normal = normals
+            .iter()
+            .filter_map(|pair| match pair {
+                (Some(a), Some(b)) => Some(Normal::normal(a.clone(), b.clone()).normalize()),
+                _ => None,
+            })
+            .find(|normal| !normal.is_zero())
+            .unwrap_or_else(Vector::zero);
+        Some(WithNormal { point: p2, normal })
     }
 }
 
@@ -90,3 +103,33 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::WithNormalIterator;
+    use crate::geometry::Vector;
+
+    #[test]
+    fn degenerate() {
+        // Turning back, neighbours of the middle point coincide
+        let data = vec![
+            Vector::new(0.0, 0.0),
+            Vector::new(1.0, 0.0),
+            Vector::new(0.0, 0.0),
+        ];
+        let normals: Vec<_> = WithNormalIterator::new(data.into_iter())
+            .map(|p| p.normal)
+            .collect();
+        assert_eq!(normals[1], Vector::new(0.0, -1.0));
+
+        let resting: Vec<_> = WithNormalIterator::new(vec![Vector::new(1.0, 1.0); 2].into_iter())
+            .map(|p| p.normal)
+            .collect();
+        assert_eq!(resting, vec![Vector::new(0.0, 0.0); 2]);
+        assert_eq!(Vector::new(0.0, 0.0).normalize(), Vector::new(0.0, 0.0));
+        assert_eq!(
+            Vector::new(f32::NAN, 0.0).normalize(),
+            Vector::new(0.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use crate::dynamics::Dynamics;
+use crate::json::{Json, JsonError};
+use crate::tool::{tool_with_parameters, Tool};
+use std::convert::TryInto;
+use std::fmt;
+use wasm_bindgen::JsValue;
+
+/// First bytes of every preset
+const MAGIC: &[u8; 8] = b"ARTBRUSH";
+/// Version written by `Preset::to_bytes`, older versions are still readable
+pub const PRESET_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PresetError {
+    pub message: String,
+}
+
+impl PresetError {
+    pub fn new(message: impl Into<String>) -> PresetError {
+        PresetError {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<JsonError> for PresetError {
+    fn from(error: JsonError) -> Self {
+        PresetError::new(error.message)
+    }
+}
+
+impl From<PresetError> for JsValue {
+    fn from(error: PresetError) -> Self {
+        JsValue::from_str(&error.message)
+    }
+}
+
+/// Tip or texture of a tool, straight sRGB RGBA with 8 bits per channel
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PresetImage {
+    /// Meaning of the image for the tool, like `tip`
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Configured tool, stored as
+///
+/// - `ARTBRUSH` magic
+/// - manifest length, little endian u32
+/// - manifest, UTF-8 JSON
+///   `{"version", "tool", "parameters": {...}, "dynamics": {...}, "images": [...]}`
+/// - pixels of images, in the order of the manifest
+#[derive(Clone, Debug, PartialEq)]
+pub struct Preset {
+    pub tool: String,
+    /// Object with values of tool parameters
+    pub parameters: Json,
+    pub dynamics: Dynamics,
+    pub images: Vec<PresetImage>,
+}
+
+impl Preset {
+    pub fn from_tool(tool: &dyn Tool) -> Preset {
+        Preset {
+            tool: tool.name().to_string(),
+            parameters: tool.parameters(),
+            dynamics: tool.dynamics().clone(),
+            images: tool.images(),
+        }
+    }
+
+    pub fn to_tool(&self) -> Result<Box<dyn Tool>, PresetError> {
+        let mut tool = tool_with_parameters(&self.tool, &self.parameters)?;
+        tool.set_dynamics(self.dynamics.clone())?;
+        for image in self.images.iter() {
+            if !tool.set_image(image.clone()) {
+                return Err(PresetError::new(format!(
+                    "Unknown image \"{}\" of {}",
+                    image.name, self.tool
+                )));
+            }
+        }
+        Ok(tool)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let manifest = Json::object()
+            .with("version", PRESET_VERSION)
+            .with("tool", self.tool.as_str())
+            .with("parameters", self.parameters.clone())
+            .with("dynamics", self.dynamics.to_json())
+            .with(
+                "images",
+                Json::Array(
+                    self.images
+                        .iter()
+                        .map(|image| {
+                            Json::object()
+                                .with("name", image.name.as_str())
+                                .with("width", image.width)
+                                .with("height", image.height)
+                        })
+                        .collect(),
+                ),
+            )
+            .to_string();
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&(manifest.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(manifest.as_bytes());
+        for image in self.images.iter() {
+            bytes.extend_from_slice(&image.pixels);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Preset, PresetError> {
+        let truncated = || PresetError::new("Preset is truncated");
+        if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(PresetError::new("Not a brush preset"));
+        }
+        let header = MAGIC.len() + 4;
+        let length = u32::from_le_bytes(
+            bytes
+                .get(MAGIC.len()..header)
+                .ok_or_else(truncated)?
+                .try_into()
+                .unwrap(),
+        ) as usize;
+        let end = header.checked_add(length).ok_or_else(truncated)?;
+        let manifest = bytes.get(header..end).ok_or_else(truncated)?;
+        let manifest = std::str::from_utf8(manifest)
+            .map_err(|_| PresetError::new("Manifest must be UTF-8"))?;
+        let manifest = Json::parse(manifest)?;
+
+        let version = manifest.f32_field("version")?;
+        if version < 1.0 || version > PRESET_VERSION as f32 {
+            return Err(PresetError::new(format!(
+                "Unsupported preset version {}",
+                version
+            )));
+        }
+
+        let mut data = &bytes[end..];
+        let mut images = Vec::new();
+        for image in manifest.array_field("images")? {
+            let width = image.f32_field("width")? as u32;
+            let height = image.f32_field("height")? as u32;
+            let size = (width as usize)
+                .checked_mul(height as usize)
+                .and_then(|pixels| pixels.checked_mul(4))
+                .ok_or_else(|| PresetError::new("Image is too large"))?;
+            if data.len() < size {
+                return Err(truncated());
+            }
+            images.push(PresetImage {
+                name: image.str_field("name")?.to_string(),
+                width,
+                height,
+                pixels: data[..size].to_vec(),
+            });
+            data = &data[size..];
+        }
+
+        Ok(Preset {
+            tool: manifest.str_field("tool")?.to_string(),
+            parameters: manifest.field("parameters")?.clone(),
+            dynamics: Dynamics::from_json(manifest.field("dynamics")?)?,
+            images,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Preset, PresetImage};
+    use crate::dynamics::{Dynamics, Sensor};
+    use crate::geometry::PressureCurve;
+    use crate::json::Json;
+    use crate::tool::tool_with_parameters;
+
+    #[test]
+    fn round_trip() {
+        let mut tool =
+            tool_with_parameters("wave_pencil", &Json::object().with("thickness", 3.0f32)).unwrap();
+        let mut dynamics = Dynamics::new();
+        dynamics.add(
+            Sensor::Pressure,
+            "thickness".into(),
+            0.5,
+            1.0,
+            0.0,
+            &PressureCurve::bezier(0.0, 0.8, 1.0, 1.0),
+        );
+        tool.set_dynamics(dynamics).unwrap();
+
+        let preset = Preset::from_tool(tool.as_ref());
+        let restored = Preset::from_bytes(&preset.to_bytes()).unwrap();
+        assert_eq!(restored, preset);
+        let restored = restored.to_tool().unwrap();
+        assert_eq!(restored.parameters(), tool.parameters());
+        assert_eq!(restored.dynamics(), tool.dynamics());
+
+        // Dynamics of parameters the tool doesn't have
+        let mut unknown = preset.clone();
+        unknown.dynamics.add(
+            Sensor::Pressure,
+            "size".into(),
+            0.0,
+            1.0,
+            0.0,
+            &PressureCurve::linear(),
+        );
+        assert!(unknown.to_tool().is_err());
+    }
+
+    #[test]
+    fn images() {
+        let mut preset = Preset::from_tool(
+            tool_with_parameters("wave_pencil", &Json::object())
+                .unwrap()
+                .as_ref(),
+        );
+        preset.images = vec![
+            PresetImage {
+                name: "tip".into(),
+                width: 2,
+                height: 1,
+                pixels: vec![1, 2, 3, 4, 5, 6, 7, 8],
+            },
+            PresetImage {
+                name: "texture".into(),
+                width: 1,
+                height: 1,
+                pixels: vec![9, 10, 11, 12],
+            },
+        ];
+        let bytes = preset.to_bytes();
+        assert_eq!(Preset::from_bytes(&bytes), Ok(preset.clone()));
+        // Wave pencil has no images
+        assert!(preset.to_tool().is_err());
+
+        assert!(Preset::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Preset::from_bytes(b"ARTBRUSH").is_err());
+        assert!(Preset::from_bytes(b"{}").is_err());
+    }
+
+    #[test]
+    fn version() {
+        let manifest = r#"{"version":2,"tool":"wave_pencil","parameters":{},"dynamics":{"mappings":[]},"images":[]}"#;
+        let mut bytes = b"ARTBRUSH".to_vec();
+        bytes.extend_from_slice(&(manifest.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(manifest.as_bytes());
+        assert!(Preset::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn overflow() {
+        let mut bytes = b"ARTBRUSH".to_vec();
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Preset::from_bytes(&bytes).is_err());
+
+        let manifest = r#"{"version":1,"tool":"wave_pencil","parameters":{},"dynamics":{"mappings":[]},"images":[{"name":"a","width":4294967295,"height":4294967295}]}"#;
+        let mut bytes = b"ARTBRUSH".to_vec();
+        bytes.extend_from_slice(&(manifest.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(manifest.as_bytes());
+        assert!(Preset::from_bytes(&bytes).is_err());
+    }
+}
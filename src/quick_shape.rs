@@ -0,0 +1,705 @@
+use crate::geometry::{Distance, Mix, Touch, Transform, Vector};
+use crate::touch_list::TouchList;
+use std::f32::consts::PI;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShapeKind {
+    Line = 0,
+    Arc = 1,
+    Circle = 2,
+    Ellipse = 3,
+    Rectangle = 4,
+    Triangle = 5,
+    Polygon = 6,
+}
+
+/// Stroke recognized as a shape, see `recognize_shape`
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuickShape {
+    kind: ShapeKind,
+    confidence: f32,
+    path: TouchList,
+}
+
+#[wasm_bindgen]
+impl QuickShape {
+    pub fn kind(&self) -> ShapeKind {
+        self.kind
+    }
+
+    /// How well the shape matches the stroke, in range [0, 1]
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+
+    /// The stroke moved onto the shape, touches keep their pressure and tilt
+    pub fn path(&self) -> TouchList {
+        self.path.clone()
+    }
+}
+
+/// Root mean square distance from the shape giving zero confidence, relative to the stroke size
+const TOLERANCE: f32 = 0.08;
+/// Distance between stroke ends, relative to the stroke size, for the stroke to be closed
+const CLOSED_DISTANCE: f32 = 0.2;
+/// Corner detection tolerance, relative to the stroke size
+const CORNER_TOLERANCE: f32 = 0.06;
+/// Polygons with more vertices are treated as curves
+const MAX_POLYGON_VERTICES: usize = 8;
+
+/// Ideal curve of a shape, parametrized by [0, 1]
+#[derive(Clone, Debug, PartialEq)]
+enum Outline {
+    /// Unit circle arc from `start` angle by `sweep` radians, mapped by `transform`
+    Ellipse {
+        transform: Transform,
+        start: f32,
+        sweep: f32,
+    },
+    /// Parametrized by length, closed polylines start at `start` length from the first point
+    Polyline {
+        points: Vec<Vector>,
+        closed: bool,
+        start: f32,
+    },
+}
+
+impl Outline {
+    fn polyline(points: Vec<Vector>, closed: bool, first: Vector) -> Outline {
+        let mut outline = Outline::Polyline {
+            points,
+            closed,
+            start: 0.0,
+        };
+        if closed {
+            let start = outline.project(first);
+            if let Outline::Polyline { start: s, .. } = &mut outline {
+                *s = start;
+            }
+        }
+        outline
+    }
+
+    /// Segments of a polyline with their start lengths
+    fn segments(points: &[Vector], closed: bool) -> Vec<(Vector, Vector, f32)> {
+        let mut result = Vec::new();
+        let mut length = 0.0;
+        let count = if closed {
+            points.len()
+        } else {
+            points.len() - 1
+        };
+        for i in 0..count {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            result.push((a, b, length));
+            length += a.distance(b);
+        }
+        result
+    }
+
+    fn perimeter(points: &[Vector], closed: bool) -> f32 {
+        Outline::segments(points, closed)
+            .last()
+            .map_or(0.0, |(a, b, s)| s + a.distance(*b))
+    }
+
+    /// Length position of the nearest polyline point
+    fn project(&self, point: Vector) -> f32 {
+        match self {
+            Outline::Ellipse { .. } => 0.0,
+            Outline::Polyline { points, closed, .. } => {
+                Outline::segments(points, *closed)
+                    .into_iter()
+                    .map(|(a, b, s)| {
+                        let t = segment_parameter(a, b, point);
+                        (a.mix(b, t).distance(point), s + a.distance(b) * t)
+                    })
+                    .fold((f32::INFINITY, 0.0), |m, v| if v.0 < m.0 { v } else { m })
+                    .1
+            }
+        }
+    }
+
+    fn point_at(&self, t: f32) -> Vector {
+        match self {
+            Outline::Ellipse {
+                transform,
+                start,
+                sweep,
+            } => {
+                let (sin, cos) = (start + sweep * t).sin_cos();
+                transform.apply_point(Vector::new(cos, sin))
+            }
+            Outline::Polyline {
+                points,
+                closed,
+                start,
+            } => {
+                let perimeter = Outline::perimeter(points, *closed);
+                let mut position = start + perimeter * t;
+                if *closed {
+                    position = position.rem_euclid(perimeter.max(f32::EPSILON));
+                }
+                let segments = Outline::segments(points, *closed);
+                let (a, b, s) = segments
+                    .iter()
+                    .rev()
+                    .find(|(_, _, s)| *s <= position)
+                    .unwrap_or(&segments[0]);
+                let length = a.distance(*b);
+                if length <= f32::EPSILON {
+                    *a
+                } else {
+                    a.mix(*b, ((position - s) / length).clamp(0.0, 1.0))
+                }
+            }
+        }
+    }
+
+    /// Parameters of polyline vertices, so they can be added to the path
+    fn corners(&self) -> Vec<f32> {
+        match self {
+            Outline::Ellipse { .. } => Vec::new(),
+            Outline::Polyline {
+                points,
+                closed,
+                start,
+            } => {
+                let perimeter = Outline::perimeter(points, *closed);
+                if perimeter <= f32::EPSILON {
+                    return Vec::new();
+                }
+                Outline::segments(points, *closed)
+                    .iter()
+                    .map(|(_, _, s)| (s - start).rem_euclid(perimeter) / perimeter)
+                    .filter(|t| *t > 0.0 && *t < 1.0)
+                    .collect()
+            }
+        }
+    }
+}
+
+fn segment_parameter(a: Vector, b: Vector, point: Vector) -> f32 {
+    let d = b - a;
+    let length = d.dot(d);
+    if length <= f32::EPSILON {
+        0.0
+    } else {
+        ((point - a).dot(d) / length).clamp(0.0, 1.0)
+    }
+}
+
+fn distance_to_segment(a: Vector, b: Vector, point: Vector) -> f32 {
+    a.mix(b, segment_parameter(a, b, point)).distance(point)
+}
+
+fn rms(values: impl Iterator<Item = f32>) -> f32 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v * v, count + 1));
+    (sum / count.max(1) as f32).sqrt()
+}
+
+fn signed_area(points: &[Vector]) -> f32 {
+    (0..points.len())
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f32>()
+        * 0.5
+}
+
+/// Solves a 3x3 linear system with Cramer's rule
+fn solve3(m: [[f32; 3]; 3], v: [f32; 3]) -> Option<[f32; 3]> {
+    let det = |m: [[f32; 3]; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+    let d = det(m);
+    if d.abs() <= f32::EPSILON {
+        return None;
+    }
+    let mut result = [0.0; 3];
+    for (i, r) in result.iter_mut().enumerate() {
+        let mut mi = m;
+        for row in 0..3 {
+            mi[row][i] = v[row];
+        }
+        *r = det(mi) / d;
+    }
+    Some(result)
+}
+
+struct Stroke {
+    points: Vec<Vector>,
+    /// Center of mass
+    center: Vector,
+    /// Diagonal of the bounding box
+    size: f32,
+    closed: bool,
+}
+
+impl Stroke {
+    fn new(points: Vec<Vector>) -> Stroke {
+        let center = points.iter().fold(Vector::new(0.0, 0.0), |sum, p| sum + *p)
+            * (1.0 / points.len() as f32);
+        let (min, max) = points.iter().fold((points[0], points[0]), |(min, max), p| {
+            (
+                Vector::new(min.x.min(p.x), min.y.min(p.y)),
+                Vector::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        });
+        let size = min.distance(max);
+        let closed = points[0].distance(points[points.len() - 1]) < size * CLOSED_DISTANCE;
+        Stroke {
+            points,
+            center,
+            size,
+            closed,
+        }
+    }
+
+    fn first(&self) -> Vector {
+        self.points[0]
+    }
+
+    fn last(&self) -> Vector {
+        self.points[self.points.len() - 1]
+    }
+
+    /// Direction of the largest spread of points
+    fn principal_angle(&self) -> f32 {
+        let (sxx, sxy, syy) = self.points.iter().fold((0.0, 0.0, 0.0), |(xx, xy, yy), p| {
+            let d = *p - self.center;
+            (xx + d.x * d.x, xy + d.x * d.y, yy + d.y * d.y)
+        });
+        0.5 * (2.0 * sxy).atan2(sxx - syy)
+    }
+
+    fn line(&self) -> (ShapeKind, f32, Outline) {
+        let (sin, cos) = self.principal_angle().sin_cos();
+        let direction = Vector::new(cos, sin);
+        let project = |p: Vector| self.center + direction * (p - self.center).dot(direction);
+        let error = rms(self
+            .points
+            .iter()
+            .map(|p| (*p - self.center).dot(direction.normal())));
+        let outline = Outline::polyline(
+            vec![project(self.first()), project(self.last())],
+            false,
+            self.first(),
+        );
+        (ShapeKind::Line, error, outline)
+    }
+
+    /// Circle fit minimizing algebraic distance (Kåsa), `None` for collinear points
+    fn fit_circle(&self) -> Option<(Vector, f32)> {
+        let mut m = [[0.0; 3]; 3];
+        let mut v = [0.0; 3];
+        for p in self.points.iter() {
+            // Relative to the center of mass for precision
+            let d = *p - self.center;
+            let row = [d.x, d.y, 1.0];
+            let z = -(d.x * d.x + d.y * d.y);
+            for i in 0..3 {
+                for j in 0..3 {
+                    m[i][j] += row[i] * row[j];
+                }
+                v[i] += row[i] * z;
+            }
+        }
+        let [a, b, c] = solve3(m, v)?;
+        let radius = (a * a / 4.0 + b * b / 4.0 - c).sqrt();
+        if !radius.is_finite() || radius > self.size * 100.0 {
+            return None;
+        }
+        Some((self.center + Vector::new(-a / 2.0, -b / 2.0), radius))
+    }
+
+    fn circle(&self) -> Option<(ShapeKind, f32, Outline)> {
+        let (center, radius) = self.fit_circle()?;
+        let error = rms(self.points.iter().map(|p| p.distance(center) - radius));
+        let angle = |p: Vector| (p.y - center.y).atan2(p.x - center.x);
+        let start = angle(self.first());
+        // Unwrapped angle travelled by the stroke
+        let sweep: f32 = self
+            .points
+            .windows(2)
+            .map(|w| {
+                let d = angle(w[1]) - angle(w[0]);
+                (d + PI).rem_euclid(2.0 * PI) - PI
+            })
+            .sum();
+        let transform = Transform::translate(center) * Transform::scale(radius, radius);
+        Some(if self.closed {
+            (
+                ShapeKind::Circle,
+                error,
+                Outline::Ellipse {
+                    transform,
+                    start,
+                    sweep: 2.0 * PI * sweep.signum(),
+                },
+            )
+        } else {
+            (
+                ShapeKind::Arc,
+                error,
+                Outline::Ellipse {
+                    transform,
+                    start,
+                    sweep,
+                },
+            )
+        })
+    }
+
+    fn ellipse(&self) -> Option<(ShapeKind, f32, Outline)> {
+        let angle = self.principal_angle();
+        let to_local = Transform::rotate(-angle) * Transform::translate(-self.center);
+        let local: Vec<_> = self
+            .points
+            .iter()
+            .map(|p| to_local.apply_point(*p))
+            .collect();
+        // x² A + y² B = 1, least squares
+        let (mut xxxx, mut xxyy, mut yyyy, mut xx, mut yy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for p in local.iter() {
+            let (x2, y2) = (p.x * p.x, p.y * p.y);
+            xxxx += x2 * x2;
+            xxyy += x2 * y2;
+            yyyy += y2 * y2;
+            xx += x2;
+            yy += y2;
+        }
+        let det = xxxx * yyyy - xxyy * xxyy;
+        if det.abs() <= f32::EPSILON {
+            return None;
+        }
+        let a = (xx * yyyy - yy * xxyy) / det;
+        let b = (yy * xxxx - xx * xxyy) / det;
+        if a <= 0.0 || b <= 0.0 {
+            return None;
+        }
+        let (rx, ry) = (1.0 / a.sqrt(), 1.0 / b.sqrt());
+        let error = rms(local.iter().map(|p| {
+            let r = (p.x * p.x * a + p.y * p.y * b).sqrt();
+            if r <= f32::EPSILON {
+                rx.min(ry)
+            } else {
+                (1.0 - 1.0 / r) * p.length()
+            }
+        }));
+        let first = local[0];
+        Some((
+            ShapeKind::Ellipse,
+            error,
+            Outline::Ellipse {
+                transform: Transform::translate(self.center)
+                    * Transform::rotate(angle)
+                    * Transform::scale(rx, ry),
+                start: (first.y / ry).atan2(first.x / rx),
+                sweep: 2.0 * PI * signed_area(&local).signum(),
+            },
+        ))
+    }
+
+    /// Corners of a closed stroke in drawing order, Douglas-Peucker simplification
+    fn corners(&self) -> Vec<Vector> {
+        fn simplify(points: &[Vector], tolerance: f32, result: &mut Vec<Vector>) {
+            let (a, b) = (points[0], points[points.len() - 1]);
+            let farthest = (1..points.len().saturating_sub(1))
+                .map(|i| (i, distance_to_segment(a, b, points[i])))
+                .fold((0, 0.0), |m, v| if v.1 > m.1 { v } else { m });
+            if farthest.1 > tolerance {
+                simplify(&points[..=farthest.0], tolerance, result);
+                simplify(&points[farthest.0..], tolerance, result);
+            } else {
+                result.push(b);
+            }
+        }
+
+        let tolerance = self.size * CORNER_TOLERANCE;
+        // Split at the point farthest from the start, so both halves are open
+        let far = (0..self.points.len())
+            .max_by(|a, b| {
+                let d = |i: usize| self.points[i].distance(self.first());
+                d(*a).total_cmp(&d(*b))
+            })
+            .unwrap_or(0);
+        let mut closed = self.points.clone();
+        closed.push(self.first());
+        let mut corners = vec![self.first()];
+        simplify(&closed[..=far], tolerance, &mut corners);
+        simplify(&closed[far..], tolerance, &mut corners);
+        // The last corner is the start again
+        corners.pop();
+
+        // The stroke may start in the middle of an edge
+        if corners.len() > 3 {
+            let (previous, next) = (corners[corners.len() - 1], corners[1]);
+            if distance_to_segment(previous, next, corners[0]) <= tolerance {
+                corners.remove(0);
+            }
+        }
+        corners
+    }
+
+    fn polygon_error(&self, corners: &[Vector]) -> f32 {
+        rms(self.points.iter().map(|p| {
+            (0..corners.len())
+                .map(|i| distance_to_segment(corners[i], corners[(i + 1) % corners.len()], *p))
+                .fold(f32::INFINITY, f32::min)
+        }))
+    }
+
+    fn polygons(&self) -> Vec<(ShapeKind, f32, Outline)> {
+        let corners = self.corners();
+        let mut result = Vec::new();
+        if corners.len() < 3 || corners.len() > MAX_POLYGON_VERTICES {
+            return result;
+        }
+        let kind = match corners.len() {
+            3 => ShapeKind::Triangle,
+            _ => ShapeKind::Polygon,
+        };
+        result.push((
+            kind,
+            self.polygon_error(&corners),
+            Outline::polyline(corners.clone(), true, self.first()),
+        ));
+        if corners.len() == 4 {
+            if let Some(rectangle) = rectangle(&corners) {
+                result.push((
+                    ShapeKind::Rectangle,
+                    self.polygon_error(&rectangle),
+                    Outline::polyline(rectangle, true, self.first()),
+                ));
+            }
+        }
+        result
+    }
+}
+
+/// Rectangle closest to a quadrilateral, corners in the same order
+fn rectangle(corners: &[Vector]) -> Option<Vec<Vector>> {
+    // Average edge direction modulo a right angle
+    let (sin, cos) = (0..4)
+        .map(|i| {
+            let d = corners[(i + 1) % 4] - corners[i];
+            (4.0 * d.y.atan2(d.x)).sin_cos()
+        })
+        .fold((0.0, 0.0), |(s, c), (sin, cos)| (s + sin, c + cos));
+    let angle = sin.atan2(cos) / 4.0;
+    let to_local = Transform::rotate(-angle);
+    let local: Vec<_> = corners.iter().map(|p| to_local.apply_point(*p)).collect();
+    let center = local.iter().fold(Vector::new(0.0, 0.0), |s, p| s + *p) * 0.25;
+    let half = local.iter().fold(Vector::new(0.0, 0.0), |s, p| {
+        s + Vector::new((p.x - center.x).abs(), (p.y - center.y).abs())
+    }) * 0.25;
+    let from_local = Transform::rotate(angle);
+    let result: Vec<_> = local
+        .iter()
+        .map(|p| {
+            let corner = Vector::new(
+                center.x + half.x * (p.x - center.x).signum(),
+                center.y + half.y * (p.y - center.y).signum(),
+            );
+            from_local.apply_point(corner)
+        })
+        .collect();
+    // Every corner of the quadrilateral must map to a different corner
+    let distinct = (0..4).all(|i| (i + 1..4).all(|j| result[i].distance(result[j]) > f32::EPSILON));
+    if distinct {
+        Some(result)
+    } else {
+        None
+    }
+}
+
+/// Complexity of shapes, simpler shapes win when fits are close
+fn penalty(kind: ShapeKind) -> f32 {
+    match kind {
+        ShapeKind::Line | ShapeKind::Circle => 0.0,
+        ShapeKind::Arc | ShapeKind::Rectangle => 0.02,
+        ShapeKind::Triangle | ShapeKind::Ellipse => 0.04,
+        ShapeKind::Polygon => 0.1,
+    }
+}
+
+/// Moves touches onto the outline keeping their position along the stroke,
+/// polyline corners are added with interpolated pressure.
+/// Closed shapes end where they start, so the last touch is dropped and the path is closed.
+fn clean(path: &TouchList, outline: &Outline, closed: bool) -> TouchList {
+    let mut length = 0.0;
+    let mut positions = vec![0.0];
+    for w in path.windows(2) {
+        length += w[0].distance(w[1]);
+        positions.push(length);
+    }
+    let mut touches: Vec<(f32, Touch)> = path
+        .iter()
+        .zip(positions.iter())
+        .map(|(touch, p)| (p / length, *touch))
+        .collect();
+    for t in outline.corners() {
+        let index = touches.partition_point(|(s, _)| *s <= t);
+        let (s1, t1) = touches[index - 1];
+        let (s2, t2) = touches[index];
+        let touch = t1.mix(t2, (t - s1) / (s2 - s1).max(f32::EPSILON));
+        touches.insert(index, (t, touch));
+    }
+
+    let mut result = path.clone();
+    result.clear();
+    result.extend(touches.into_iter().map(|(t, touch)| Touch {
+        point: outline.point_at(t),
+        ..touch
+    }));
+    if closed {
+        result.pop();
+        result.set_closed(true);
+    }
+    result
+}
+
+/// Recognizes a stroke as a shape, `None` if the stroke is too short
+#[wasm_bindgen]
+pub fn recognize_shape(path: &TouchList) -> Option<QuickShape> {
+    if path.len() < 3 {
+        return None;
+    }
+    let stroke = Stroke::new(path.iter().map(|t| t.point).collect());
+    if stroke.size <= f32::EPSILON {
+        return None;
+    }
+
+    let mut candidates = Vec::new();
+    if stroke.closed {
+        candidates.extend(stroke.circle());
+        candidates.extend(stroke.ellipse());
+        candidates.extend(stroke.polygons());
+    } else {
+        candidates.push(stroke.line());
+        candidates.extend(stroke.circle());
+    }
+
+    let confidence = |error: f32| (1.0 - error / (stroke.size * TOLERANCE)).clamp(0.0, 1.0);
+    let (kind, error, outline) = candidates.into_iter().max_by(|a, b| {
+        let score = |c: &(ShapeKind, f32, Outline)| confidence(c.1) - penalty(c.0);
+        score(a).total_cmp(&score(b))
+    })?;
+    Some(QuickShape {
+        kind,
+        confidence: confidence(error),
+        path: clean(path, &outline, stroke.closed),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{recognize_shape, ShapeKind};
+    use crate::geometry::{Distance, Mix, Vector};
+    use crate::touch_list::TouchList;
+    use std::f32::consts::PI;
+
+    fn path(points: impl IntoIterator<Item = Vector>) -> TouchList {
+        let mut path = TouchList::new(1.0);
+        for (i, p) in points.into_iter().enumerate() {
+            path.push(p.x, p.y, 0.3 + (i % 5) as f32 * 0.1);
+        }
+        path
+    }
+
+    /// Points along a closed polygon, starting in the middle of the first edge
+    fn polygon(corners: &[Vector]) -> TouchList {
+        let n = corners.len();
+        path((0..=n * 10).map(|i| {
+            let t = (i as f32 / 10.0 + 0.5) % n as f32;
+            let edge = t as usize;
+            corners[edge].mix(corners[(edge + 1) % n], t - edge as f32)
+        }))
+    }
+
+    fn ellipse(rx: f32, ry: f32, sweep: f32) -> TouchList {
+        path((0..=40).map(|i| {
+            let a = sweep * i as f32 / 40.0;
+            Vector::new(100.0 + rx * a.cos(), 50.0 + ry * a.sin())
+        }))
+    }
+
+    fn recognize(path: &TouchList) -> ShapeKind {
+        let shape = recognize_shape(path).unwrap();
+        assert!(shape.confidence() > 0.9, "{:?}", shape);
+        shape.kind()
+    }
+
+    #[test]
+    fn shapes() {
+        let wobbly_line = path((0..20).map(|i| Vector::new(i as f32 * 5.0, (i % 2) as f32)));
+        assert_eq!(recognize(&wobbly_line), ShapeKind::Line);
+        assert_eq!(recognize(&ellipse(40.0, 40.0, PI * 0.7)), ShapeKind::Arc);
+        assert_eq!(recognize(&ellipse(40.0, 40.0, PI * 2.0)), ShapeKind::Circle);
+        assert_eq!(
+            recognize(&ellipse(60.0, 20.0, PI * 2.0)),
+            ShapeKind::Ellipse
+        );
+
+        let rectangle = [
+            Vector::new(0.0, 0.0),
+            Vector::new(80.0, 0.0),
+            Vector::new(80.0, 40.0),
+            Vector::new(0.0, 40.0),
+        ];
+        assert_eq!(recognize(&polygon(&rectangle)), ShapeKind::Rectangle);
+        let triangle = [
+            Vector::new(0.0, 0.0),
+            Vector::new(80.0, 10.0),
+            Vector::new(30.0, 60.0),
+        ];
+        assert_eq!(recognize(&polygon(&triangle)), ShapeKind::Triangle);
+        let pentagon: Vec<_> = (0..5)
+            .map(|i| {
+                let a = 2.0 * PI * i as f32 / 5.0;
+                Vector::new(50.0 * a.cos(), 50.0 * a.sin())
+            })
+            .collect();
+        assert_eq!(recognize(&polygon(&pentagon)), ShapeKind::Polygon);
+
+        assert!(recognize_shape(&path(vec![Vector::new(1.0, 1.0); 2])).is_none());
+    }
+
+    #[test]
+    fn cleaned_path() {
+        let mut stroke = ellipse(40.0, 40.0, PI * 2.0);
+        stroke[7].point = stroke[7].point + Vector::new(3.0, 0.0);
+        let shape = recognize_shape(&stroke).unwrap();
+        let cleaned = shape.path();
+        assert_eq!(cleaned.len(), stroke.len() - 1);
+        assert!(cleaned.closed());
+        for (a, b) in cleaned.iter().zip(stroke.iter()) {
+            assert_eq!(a.pressure, b.pressure);
+            assert!((a.point.distance(Vector::new(100.0, 50.0)) - 40.0).abs() < 0.5);
+        }
+        assert!(!recognize_shape(&ellipse(40.0, 40.0, PI))
+            .unwrap()
+            .path()
+            .closed());
+
+        // Corners are added
+        let square = [
+            Vector::new(0.0, 0.0),
+            Vector::new(40.0, 0.0),
+            Vector::new(40.0, 40.0),
+            Vector::new(0.0, 40.0),
+        ];
+        let stroke = polygon(&square);
+        let cleaned = recognize_shape(&stroke).unwrap().path();
+        for corner in square.iter() {
+            assert!(cleaned.iter().any(|t| t.point.distance(*corner) < 1e-3));
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use crate::geometry::{Distance, Mix, Vector};
+use crate::path::WithOffset;
+
+/// PCG32 random generator, see https://www.pcg-random.org
+///
+/// Only integer operations and basic float arithmetic are used, no transcendental functions,
+/// so the same seed gives bit-identical results in every browser and in native builds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Random {
+    state: u64,
+    increment: u64,
+}
+
+const MULTIPLIER: u64 = 6364136223846793005;
+/// Stream selector, the same for all generators, only the seed differs
+const STREAM: u64 = 721347520444481703;
+
+impl Random {
+    pub fn new(seed: i32) -> Random {
+        Random::with_stream(seed as u32 as u64, STREAM)
+    }
+
+    fn with_stream(seed: u64, stream: u64) -> Random {
+        let mut random = Random {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        random.next_u32();
+        random.state = random.state.wrapping_add(seed);
+        random.next_u32();
+        random
+    }
+
+    /// Independent generator for a sub-task, so adding random calls in one place
+    /// doesn't change sequences in another
+    pub fn fork(&mut self) -> Random {
+        Random::new(self.next_u32() as i32)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let state = self.state;
+        self.state = state.wrapping_mul(MULTIPLIER).wrapping_add(self.increment);
+        let xor_shifted = (((state >> 18) ^ state) >> 27) as u32;
+        let rotation = (state >> 59) as u32;
+        xor_shifted.rotate_right(rotation)
+    }
+
+    /// Uniform value in range [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        // 24 bits fit into f32 mantissa exactly
+        (self.next_u32() >> 8) as f32 * (1.0 / (1 << 24) as f32)
+    }
+
+    /// Uniform value in range [min, max)
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// Normal distribution approximated by the sum of 12 uniform values (Irwin-Hall),
+    /// values are limited to `mean ± 6 * deviation`
+    #[allow(dead_code)] // No tool scatters with it yet
+    pub fn gaussian(&mut self, mean: f32, deviation: f32) -> f32 {
+        let sum: f32 = (0..12).map(|_| self.next_f32()).sum();
+        mean + (sum - 6.0) * deviation
+    }
+
+    /// Uniform point inside of the unit circle
+    pub fn disk(&mut self) -> Vector {
+        loop {
+            let v = Vector::new(self.range(-1.0, 1.0), self.range(-1.0, 1.0));
+            if v.x * v.x + v.y * v.y < 1.0 {
+                return v;
+            }
+        }
+    }
+
+    /// Uniform point inside of the circle
+    pub fn in_circle(&mut self, center: Vector, radius: f32) -> Vector {
+        center + self.disk() * radius
+    }
+
+    /// Point uniformly distributed along the path by length, `None` if the path is empty
+    #[allow(dead_code)] // No tool scatters along paths yet
+    pub fn on_path<T>(&mut self, path: &[WithOffset<T>]) -> Option<T>
+    where
+        T: Distance<Output = f32> + Mix<Fraction = f32> + Clone,
+    {
+        let length = path.last()?.offset;
+        let offset = self.range(0.0, length);
+        let index = path.partition_point(|p| p.offset <= offset);
+        if index == 0 || index >= path.len() {
+            return Some(path[index.min(path.len() - 1)].point.clone());
+        }
+        let (p1, p2) = (&path[index - 1], &path[index]);
+        let t = (offset - p1.offset) / (p2.offset - p1.offset);
+        Some(p1.point.clone().mix(p2.point.clone(), t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Random;
+    use crate::geometry::Vector;
+    use crate::path::WithOffsetIterator;
+
+    #[test]
+    fn reference_sequence() {
+        // Output of pcg32-demo from the reference implementation
+        let mut random = Random::with_stream(42, 54);
+        let values: Vec<u32> = (0..6).map(|_| random.next_u32()).collect();
+        assert_eq!(
+            values,
+            [0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]
+        );
+    }
+
+    #[test]
+    fn distributions() {
+        let mut random = Random::new(7);
+        let n = 10000;
+        let uniform: Vec<f32> = (0..n).map(|_| random.next_f32()).collect();
+        assert!(uniform.iter().all(|v| (0.0..1.0).contains(v)));
+        assert!((uniform.iter().sum::<f32>() / n as f32 - 0.5).abs() < 0.01);
+
+        let gaussian: Vec<f32> = (0..n).map(|_| random.gaussian(2.0, 0.5)).collect();
+        let mean = gaussian.iter().sum::<f32>() / n as f32;
+        let variance = gaussian.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n as f32;
+        assert!((mean - 2.0).abs() < 0.02);
+        assert!((variance.sqrt() - 0.5).abs() < 0.02);
+
+        let disk: Vec<Vector> = (0..n).map(|_| random.disk()).collect();
+        assert!(disk.iter().all(|v| v.length() < 1.0));
+        // A quarter of the area is within half of the radius
+        let inner = disk.iter().filter(|v| v.length() < 0.5).count();
+        assert!((2300..2700).contains(&inner));
+
+        let center = Vector::new(3.0, -2.0);
+        assert!((0..n).all(|_| (random.in_circle(center, 2.0) - center).length() < 2.0));
+    }
+
+    #[test]
+    fn deterministic() {
+        let path: Vec<_> =
+            WithOffsetIterator::new(vec![Vector::new(0.0, 0.0), Vector::new(2.0, 1.0)].into_iter())
+                .collect();
+        let sample = |seed: i32| {
+            let mut random = Random::new(seed);
+            (0..100)
+                .map(|_| (random.gaussian(0.0, 1.0), random.on_path(&path).unwrap()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sample(5), sample(5));
+        assert_ne!(sample(5), sample(6));
+    }
+
+    #[test]
+    fn on_path() {
+        let path: Vec<_> = WithOffsetIterator::new(
+            vec![
+                Vector::new(0.0, 0.0),
+                Vector::new(1.0, 0.0),
+                Vector::new(1.0, 3.0),
+            ]
+            .into_iter(),
+        )
+        .collect();
+        let mut random = Random::new(1);
+        let points: Vec<_> = (0..1000).map(|_| random.on_path(&path).unwrap()).collect();
+        assert!(points.iter().all(|p| p.x == 1.0 || p.y == 0.0));
+        // Three quarters of the length is on the vertical segment
+        let vertical = points.iter().filter(|p| p.y > 0.0).count();
+        assert!((650..850).contains(&vertical));
+        assert_eq!(random.on_path::<Vector>(&[]), None);
+    }
+}
@@ -0,0 +1,165 @@
+use crate::frame::Frame;
+use crate::geometry::{Color, Rect};
+use crate::path::{DynamicsState, Guide, GuideIterator, Predictor};
+use crate::pool::PoolEntry;
+use crate::tool::{Tool, STROKE_CONTEXT};
+use crate::touch_list::TouchList;
+use wasm_bindgen::prelude::*;
+use webgl_rc::{GlError, Texture};
+
+/// Stroke drawn while touches arrive, see `Frame::begin_stroke`.
+///
+/// Only the part of the path which can't change anymore is drawn for good, the last
+/// `STROKE_CONTEXT` touches are drawn as a provisional tail, replaced on the next call.
+/// The tail continues with predicted touches, if prediction is enabled.
+#[wasm_bindgen]
+pub struct Stroke {
+    tool: Box<dyn Tool>,
+    color: Color,
+    phases: [f32; 3],
+    /// Kept `STROKE_CONTEXT` touches before `drawn`, so parts don't go through the whole path
+    dynamics: DynamicsState,
+    path: TouchList,
+    /// Index of the touch up to which the path is drawn for good
+    drawn: usize,
+    /// Frame content without the provisional tail, while the tail is drawn
+    committed: Option<PoolEntry<Texture>>,
+    /// Area of the provisional tail
+    tail: Rect,
+    predictor: Predictor,
+    guide: Option<Guide>,
+}
+
+#[wasm_bindgen]
+impl Stroke {
+    /// Appends touches and draws them, `frame` must be the one the stroke was started on
+    #[wasm_bindgen(catch)]
+    pub fn extend(&mut self, frame: &mut Frame, touches: &TouchList) -> Result<(), JsValue> {
+        Ok(self.extend_path(frame, touches)?)
+    }
+
+    /// Draws touches predicted up to `horizon` milliseconds ahead in the provisional tail,
+    /// prediction stops when its confidence drops below `min_confidence`, 0 `horizon`
+    /// disables prediction. Only touches pushed with `TouchList.push_at` are predicted.
+    pub fn set_prediction(&mut self, horizon: f32, min_confidence: f32) {
+        self.predictor = Predictor::new(horizon, min_confidence);
+    }
+
+    /// Constrains touches added after the call
+    pub fn set_guide(&mut self, guide: &Guide) {
+        self.guide = Some(guide.clone());
+    }
+
+    /// Draws the rest of the path for good
+    #[wasm_bindgen(catch)]
+    pub fn end(mut self, frame: &mut Frame) -> Result<(), JsValue> {
+        self.restore(frame);
+        self.draw_to(frame, self.path.len().saturating_sub(1))?;
+        Ok(())
+    }
+}
+
+impl Stroke {
+    pub(crate) fn new(tool: &dyn Tool, color: Color, phases: [f32; 3], seed: i32) -> Stroke {
+        Stroke {
+            tool: tool.clone_tool(),
+            color,
+            phases,
+            dynamics: DynamicsState::new(seed),
+            path: TouchList::new(1.0),
+            drawn: 0,
+            committed: None,
+            tail: Rect::empty(),
+            predictor: Default::default(),
+            guide: None,
+        }
+    }
+
+    pub(crate) fn extend_path(
+        &mut self,
+        frame: &mut Frame,
+        touches: &TouchList,
+    ) -> Result<(), GlError> {
+        self.restore(frame);
+        self.path.set_pixel_size(touches.pixel_size());
+        match self.guide.take() {
+            Some(guide) => {
+                let pixel_size = touches.pixel_size();
+                let mut guided = match self.path.first() {
+                    Some(first) => GuideIterator::continued(
+                        touches.iter().cloned(),
+                        guide,
+                        first.point,
+                        pixel_size,
+                    ),
+                    None => GuideIterator::new(touches.iter().cloned(), guide, pixel_size),
+                };
+                self.path.extend_timed(guided.by_ref(), touches.times());
+                // The guide may get locked to the stroke direction
+                self.guide = Some(guided.into_guide());
+            }
+            None => self
+                .path
+                .extend_timed(touches.iter().cloned(), touches.times()),
+        }
+
+        let length = self.path.len();
+        self.draw_to(frame, length.saturating_sub(1 + STROKE_CONTEXT))?;
+
+        // Predicted touches are in the path only while the tail is drawn
+        let predicted = self.predictor.predict(&self.path);
+        self.path.extend(predicted);
+        let last = self.path.len().saturating_sub(1);
+        let result = self.draw_tail(frame, last);
+        self.path.truncate(length);
+        result
+    }
+
+    fn draw_tail(&mut self, frame: &mut Frame, last: usize) -> Result<(), GlError> {
+        if last > self.drawn {
+            self.committed = Some(frame.copy_texture()?);
+            self.advance_dynamics();
+            self.tail = self.tool.draw_part(
+                frame,
+                &self.path,
+                self.drawn..last,
+                self.color,
+                self.phases,
+                &self.dynamics,
+            )?;
+            frame.mark_dirty(&self.tail);
+        }
+        Ok(())
+    }
+
+    fn advance_dynamics(&mut self) {
+        let index = self.drawn.saturating_sub(STROKE_CONTEXT);
+        self.dynamics.advance(&self.path, index);
+    }
+
+    /// Removes the provisional tail
+    fn restore(&mut self, frame: &mut Frame) {
+        if let Some(committed) = self.committed.take() {
+            frame.replace_texture(committed);
+            frame.mark_dirty(&self.tail);
+            self.tail = Rect::empty();
+        }
+    }
+
+    fn draw_to(&mut self, frame: &mut Frame, index: usize) -> Result<(), GlError> {
+        if index > self.drawn {
+            self.advance_dynamics();
+            let modified = self.tool.draw_part(
+                frame,
+                &self.path,
+                self.drawn..index,
+                self.color,
+                self.phases,
+                &self.dynamics,
+            )?;
+            frame.mark_dirty(&modified);
+            self.drawn = index;
+        }
+        Ok(())
+    }
+}
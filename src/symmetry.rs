@@ -0,0 +1,139 @@
+use crate::geometry::{Transform, Vector};
+use crate::touch_list::TouchList;
+use std::f32::consts::PI;
+use wasm_bindgen::prelude::*;
+
+/// Produces transformed copies of a path, the first copy is the path itself
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Symmetry {
+    transforms: Vec<Transform>,
+}
+
+#[wasm_bindgen]
+impl Symmetry {
+    /// The path and its mirror image across the line through (x, y)
+    /// @param angle of the axis in radians, 0 is horizontal
+    pub fn mirror(x: f32, y: f32, angle: f32) -> Symmetry {
+        let (sin, cos) = angle.sin_cos();
+        Symmetry {
+            transforms: vec![
+                Transform::identity(),
+                Transform::reflect(Vector::new(x, y), Vector::new(cos, sin)),
+            ],
+        }
+    }
+
+    /// `count` copies rotated around (x, y) by equal angles
+    pub fn radial(x: f32, y: f32, count: u32) -> Symmetry {
+        let center = Vector::new(x, y);
+        let count = count.max(1);
+        Symmetry {
+            transforms: (0..count)
+                .map(|i| {
+                    Transform::translate(center)
+                        * Transform::rotate(2.0 * PI * i as f32 / count as f32)
+                        * Transform::translate(-center)
+                })
+                .collect(),
+        }
+    }
+
+    /// Radial symmetry with every copy also mirrored, `2 * count` copies
+    /// @param angle of one of mirror axes in radians, 0 is horizontal
+    pub fn kaleidoscope(x: f32, y: f32, count: u32, angle: f32) -> Symmetry {
+        let radial = Symmetry::radial(x, y, count);
+        let mirror = Symmetry::mirror(x, y, angle).transforms[1];
+        Symmetry {
+            transforms: radial
+                .transforms
+                .iter()
+                .copied()
+                .chain(radial.transforms.iter().map(|t| *t * mirror))
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.transforms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transforms.is_empty()
+    }
+}
+
+impl Symmetry {
+    pub fn transforms(&self) -> &[Transform] {
+        &self.transforms
+    }
+
+    pub fn apply(&self, path: &TouchList) -> Vec<TouchList> {
+        self.transforms
+            .iter()
+            .map(|t| path.transformed(t))
+            .collect()
+    }
+}
+
+impl Default for Symmetry {
+    /// Only the path itself
+    fn default() -> Self {
+        Symmetry {
+            transforms: vec![Transform::identity()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Symmetry;
+    use crate::geometry::{Distance, Vector};
+    use crate::touch_list::TouchList;
+
+    fn path() -> TouchList {
+        let mut path = TouchList::new(0.5);
+        path.push(12.0, 10.0, 0.3);
+        path.push(14.0, 11.0, 0.6);
+        path
+    }
+
+    fn assert_close(a: Vector, b: Vector) {
+        assert!(a.distance(b) < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn mirror() {
+        let copies = Symmetry::mirror(10.0, 0.0, std::f32::consts::FRAC_PI_2).apply(&path());
+        assert_eq!(copies.len(), 2);
+        assert_eq!(copies[0], path());
+        assert_close(copies[1][1].point, Vector::new(6.0, 11.0));
+        assert_eq!(copies[1][1].pressure, 0.6);
+        assert_eq!(copies[1].pixel_size(), 0.5);
+    }
+
+    #[test]
+    fn radial() {
+        let copies = Symmetry::radial(10.0, 10.0, 4).apply(&path());
+        assert_eq!(copies.len(), 4);
+        assert_close(copies[1][0].point, Vector::new(10.0, 12.0));
+        assert_close(copies[2][0].point, Vector::new(8.0, 10.0));
+        assert_close(copies[3][0].point, Vector::new(10.0, 8.0));
+        assert_eq!(Symmetry::radial(0.0, 0.0, 0), Symmetry::default());
+    }
+
+    #[test]
+    fn kaleidoscope() {
+        let symmetry = Symmetry::kaleidoscope(10.0, 10.0, 3, 0.0);
+        assert_eq!(symmetry.len(), 6);
+        let copies = symmetry.apply(&path());
+        // Mirrored across the horizontal axis, then rotated
+        assert_close(copies[3][1].point, Vector::new(14.0, 9.0));
+        for copy in copies.iter() {
+            let center = Vector::new(10.0, 10.0);
+            assert!(
+                (copy[1].point.distance(center) - path()[1].point.distance(center)).abs() < 1e-4
+            );
+        }
+    }
+}
@@ -1,12 +1,90 @@
 use crate::context::Context;
+use crate::dynamics::Dynamics;
 use crate::frame::Frame;
-use crate::geometry::{Color, Touch};
+use crate::geometry::{Color, Rect, Touch};
+use crate::json::{Json, JsonError};
+use crate::path::DynamicsState;
+use crate::preset::{Preset, PresetImage};
+use crate::tools::tool_by_name;
 use crate::touch_list::TouchList;
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 use wasm_bindgen::prelude::*;
 use webgl_rc::GlError;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParameterKind {
+    Number,
+    /// Values are rounded
+    Integer,
+    /// Values are 0 or 1
+    Boolean,
+}
+
+impl ParameterKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ParameterKind::Number => "number",
+            ParameterKind::Integer => "integer",
+            ParameterKind::Boolean => "boolean",
+        }
+    }
+}
+
+/// Description of a tool parameter, so UI can build controls for any tool
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Parameter {
+    pub name: &'static str,
+    pub kind: ParameterKind,
+    pub min: f32,
+    pub max: f32,
+    pub default: f32,
+    /// Empty for unitless values
+    pub unit: &'static str,
+}
+
+impl Parameter {
+    /// Brings the value into the range of the parameter
+    pub fn clamp(self, value: f32) -> f32 {
+        let value = match self.kind {
+            ParameterKind::Number => value,
+            ParameterKind::Integer => value.round(),
+            ParameterKind::Boolean => (value != 0.0) as u32 as f32,
+        };
+        value.clamp(self.min, self.max)
+    }
+
+    pub fn to_json(self) -> Json {
+        Json::object()
+            .with("name", self.name)
+            .with("type", self.kind.name())
+            .with("min", self.min)
+            .with("max", self.max)
+            .with("default", self.default)
+            .with("unit", self.unit)
+    }
+}
+
+/// Number of touches around a part of a stroke which affect how the part is drawn,
+/// tools must not look further, see `Tool::draw_part`
+pub const STROKE_CONTEXT: usize = 3;
+
 pub trait Tool {
+    /// Name used by `Context::tool`
+    fn name(&self) -> &'static str;
+
+    fn schema(&self) -> &'static [Parameter];
+
+    /// Current value of a parameter from `schema`
+    fn parameter(&self, name: &str) -> Option<f32>;
+
+    /// Value is already brought into the range of the parameter,
+    /// names that aren't in `schema` are ignored
+    fn set_parameter(&mut self, name: &str, value: f32);
+
+    /// Returns the region of the frame modified by the tool, in pixels.
+    ///
+    /// All randomness must come from `Random::new(seed)`, so the same stroke
+    /// is replayed identically on every platform.
     fn draw(
         &self,
         frame: &mut Frame,
@@ -14,7 +92,127 @@ pub trait Tool {
         color: Color,
         phases: [f32; 3],
         seed: i32,
-    ) -> Result<(), GlError>;
+    ) -> Result<Rect, GlError>;
+
+    /// Draws the curve between touches `range.start` and `range.end` of `path` over the frame
+    /// content, adjacent parts join without gaps. Only `STROKE_CONTEXT` touches around
+    /// the range may be used, so the cost doesn't depend on the length of the path.
+    ///
+    /// `state` is the dynamics state of the stroke at a touch up to `STROKE_CONTEXT`
+    /// before `range.start`, or at the stroke start when the whole path is drawn.
+    fn draw_part(
+        &self,
+        frame: &mut Frame,
+        path: &TouchList,
+        range: Range<usize>,
+        color: Color,
+        phases: [f32; 3],
+        state: &DynamicsState,
+    ) -> Result<Rect, GlError>;
+
+    /// Draws every path whole over the frame content as parts of one stroke,
+    /// so copies made by `Symmetry` join where they cross instead of drawing over each other
+    fn draw_copies(
+        &self,
+        frame: &mut Frame,
+        paths: &[TouchList],
+        color: Color,
+        phases: [f32; 3],
+        seed: i32,
+    ) -> Result<Rect, GlError> {
+        let state = DynamicsState::new(seed);
+        paths.iter().try_fold(Rect::empty(), |rect, path| {
+            let range = 0..path.len().saturating_sub(1);
+            let modified = self.draw_part(frame, path, range, color, phases, &state)?;
+            Ok(rect.union(&modified))
+        })
+    }
+
+    /// Copy with the same parameters, so a stroke isn't affected by later changes of the tool
+    fn clone_tool(&self) -> Box<dyn Tool>;
+
+    /// Per sample modulation of tool parameters
+    fn dynamics(&self) -> &Dynamics;
+
+    fn dynamics_mut(&mut self) -> &mut Dynamics;
+
+    /// Fails if a mapping modulates a parameter that isn't in `schema`
+    fn set_dynamics(&mut self, dynamics: Dynamics) -> Result<(), JsonError> {
+        if let Some(parameter) = dynamics
+            .parameters()
+            .into_iter()
+            .find(|name| !self.schema().iter().any(|p| p.name == *name))
+        {
+            return Err(JsonError::new(format!(
+                "Unknown dynamics parameter \"{}\" of {}",
+                parameter,
+                self.name()
+            )));
+        }
+        *self.dynamics_mut() = dynamics;
+        Ok(())
+    }
+
+    /// Tips and textures stored in presets
+    fn images(&self) -> Vec<PresetImage> {
+        Vec::new()
+    }
+
+    /// Returns false if the tool doesn't use an image with this name
+    fn set_image(&mut self, _image: PresetImage) -> bool {
+        false
+    }
+}
+
+impl dyn Tool + '_ {
+    /// Array of parameter descriptions
+    pub fn schema_json(&self) -> Json {
+        Json::Array(self.schema().iter().map(|p| p.to_json()).collect())
+    }
+
+    /// Object with values of all parameters
+    pub fn parameters(&self) -> Json {
+        self.schema().iter().fold(Json::object(), |json, p| {
+            json.with(p.name, self.parameter(p.name).unwrap_or(p.default))
+        })
+    }
+
+    /// Sets parameters from an object, values out of range are clamped
+    pub fn set_parameters(&mut self, parameters: &Json) -> Result<(), JsonError> {
+        let fields = match parameters {
+            Json::Object(fields) => fields,
+            _ => return Err(JsonError::new("Parameters must be an object")),
+        };
+        for (name, value) in fields {
+            let parameter = *self
+                .schema()
+                .iter()
+                .find(|p| p.name == name)
+                .ok_or_else(|| {
+                    JsonError::new(format!("Unknown parameter \"{}\" of {}", name, self.name()))
+                })?;
+            let value = match value {
+                Json::Number(v) => *v as f32,
+                Json::Bool(v) => *v as u32 as f32,
+                _ => {
+                    return Err(JsonError::new(format!(
+                        "Parameter \"{}\" must be a number",
+                        name
+                    )))
+                }
+            };
+            self.set_parameter(name, parameter.clamp(value));
+        }
+        Ok(())
+    }
+}
+
+/// Tool with default parameters overridden by `parameters`
+pub fn tool_with_parameters(name: &str, parameters: &Json) -> Result<Box<dyn Tool>, JsonError> {
+    let mut tool =
+        tool_by_name(name).ok_or_else(|| JsonError::new(format!("Unknown tool \"{}\"", name)))?;
+    tool.set_parameters(parameters)?;
+    Ok(tool)
 }
 
 #[wasm_bindgen]
@@ -22,12 +220,52 @@ pub struct ToolRef {
     tool: Box<dyn Tool>,
 }
 
+#[wasm_bindgen]
+impl ToolRef {
+    /// Fails if a mapping modulates a parameter the tool doesn't have
+    #[wasm_bindgen(catch)]
+    pub fn set_dynamics(&mut self, dynamics: &Dynamics) -> Result<(), JsValue> {
+        Ok(self.tool.set_dynamics(dynamics.clone())?)
+    }
+
+    pub fn name(&self) -> String {
+        self.tool.name().to_string()
+    }
+
+    /// JSON array of parameter descriptions, see `Context::tool_schemas`
+    pub fn schema(&self) -> String {
+        self.tool.schema_json().to_string()
+    }
+
+    /// JSON object with values of all parameters
+    pub fn parameters(&self) -> String {
+        self.tool.parameters().to_string()
+    }
+
+    /// Tool type, parameters, dynamics and images, see `Context::tool_from_preset`
+    pub fn to_preset(&self) -> Vec<u8> {
+        Preset::from_tool(self.tool.as_ref()).to_bytes()
+    }
+
+    /// Value out of range is clamped
+    #[wasm_bindgen(catch)]
+    pub fn set_parameter(&mut self, name: &str, value: f32) -> Result<(), JsValue> {
+        Ok(self
+            .tool
+            .set_parameters(&Json::object().with(name, value))?)
+    }
+}
+
 impl ToolRef {
     pub fn new(tool: impl Tool + 'static) -> Self {
         Self {
             tool: Box::new(tool),
         }
     }
+
+    pub fn from_box(tool: Box<dyn Tool>) -> Self {
+        Self { tool }
+    }
 }
 
 impl Deref for ToolRef {
@@ -37,3 +275,53 @@ impl Deref for ToolRef {
         &self.tool
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::tool_with_parameters;
+    use crate::dynamics::{Dynamics, Sensor};
+    use crate::geometry::PressureCurve;
+    use crate::json::Json;
+
+    #[test]
+    fn parameters() {
+        let tool = tool_with_parameters(
+            "wave_pencil",
+            &Json::parse(r#"{"thickness": 4, "period": -1}"#).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(tool.name(), "wave_pencil");
+        assert_eq!(tool.parameter("thickness"), Some(4.0));
+        // Clamped to the range
+        assert_eq!(tool.parameter("period"), Some(1.0));
+
+        let defaults = tool_with_parameters("wave_pencil", &Json::object()).unwrap();
+        for parameter in defaults.schema() {
+            assert_eq!(defaults.parameter(parameter.name), Some(parameter.default));
+        }
+        assert_eq!(
+            Json::parse(&defaults.parameters().to_string()),
+            Ok(defaults.parameters())
+        );
+
+        assert!(tool_with_parameters("brush", &Json::object()).is_err());
+        assert!(tool_with_parameters("wave_pencil", &Json::object().with("size", 1.0f32)).is_err());
+        assert!(
+            tool_with_parameters("wave_pencil", &Json::object().with("thickness", "big")).is_err()
+        );
+    }
+
+    #[test]
+    fn dynamics() {
+        let mut tool = tool_with_parameters("wave_pencil", &Json::object()).unwrap();
+        let linear = PressureCurve::linear();
+        let mut dynamics = Dynamics::new();
+        dynamics.add(Sensor::Pressure, "jitter".into(), 0.0, 1.0, 0.0, &linear);
+        assert!(tool.set_dynamics(dynamics.clone()).is_ok());
+        assert_eq!(tool.dynamics(), &dynamics);
+
+        dynamics.add(Sensor::Random, "size".into(), 0.0, 1.0, 0.0, &linear);
+        assert!(tool.set_dynamics(dynamics).is_err());
+        assert_eq!(tool.dynamics().len(), 1);
+    }
+}
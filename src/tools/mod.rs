@@ -5,3 +5,16 @@ mod wave_pencil;
 pub use spray::*;
 pub use wave_brush::*;
 pub use wave_pencil::*;
+
+use crate::tool::Tool;
+
+/// Names accepted by `tool_by_name`
+pub const TOOL_NAMES: [&str; 1] = ["wave_pencil"];
+
+/// Tool with default parameters
+pub fn tool_by_name(name: &str) -> Option<Box<dyn Tool>> {
+    match name {
+        "wave_pencil" => Some(Box::new(WavePencil::default())),
+        _ => None,
+    }
+}
@@ -1,14 +1,17 @@
 use crate::context::Context;
+use crate::dynamics::Dynamics;
 use crate::frame::Frame;
-use crate::geometry::{Color, Touch, Vector};
+use crate::geometry::{Color, Rect, Touch, Vector};
 use crate::path::{
-    SmoothIterator, SplitIterator, WithNormal, WithNormalIterator, WithOffset, WithOffsetIterator,
+    CatmullRomIterator, DynamicsState, MinDistanceIterator, SmoothIterator, SplitIterator,
+    WithDynamics, WithDynamicsIterator, WithIndex, WithIndexIterator, WithNormal,
+    WithNormalIterator, WithOffset, WithOffsetIterator,
 };
 use crate::pool::{PoolEntry, PoolRequest};
-use crate::tool::Tool;
+use crate::random::Random;
+use crate::tool::{Parameter, ParameterKind, Tool, STROKE_CONTEXT};
 use crate::touch_list::TouchList;
-use std::f32::consts::PI;
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 use webgl_rc::{
     load_glsl, Attributes, BufferUsage, DepthFunction, Gl, GlError, PrimitiveType, Program,
     Settings, Texture, TextureFilter, TextureFormat, Uniforms,
@@ -16,16 +19,10 @@ use webgl_rc::{
 
 const MAX_THICKNESS_EASING_LEN: f32 = 10.0;
 const MAX_AMPLITUDE_EASING_LEN: f32 = 0.75;
-
-fn easing(t: f32) -> f32 {
-    if t >= 1.0 {
-        1.0
-    } else if t <= 0.0 {
-        0.0
-    } else {
-        ((PI * t).cos() - 1.0) * -0.5
-    }
-}
+/// Closer touches, in screen pixels, are treated as duplicates
+const MIN_TOUCH_DISTANCE: f32 = 0.01;
+/// Odd multiplier spreading seeds of jitter generators of adjacent touches
+const JITTER_SEED_STEP: i32 = 0x9e3779b1_u32 as i32;
 
 // --------------------------------------
 // Draw Shaders
@@ -46,41 +43,201 @@ struct DrawPhaseAttributes {
 // --------------------------------------
 // Pencil
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct WavePencil {
     pub thickness: f32,
     pub amplitude: f32,
     pub period: f32,
+    /// Radius in screen pixels of random offsets of touches
+    pub jitter: f32,
+    /// Modulates `thickness`
+    pub dynamics: Dynamics,
+}
+
+const SCHEMA: [Parameter; 4] = [
+    Parameter {
+        name: "thickness",
+        kind: ParameterKind::Number,
+        min: 0.1,
+        max: 100.0,
+        default: 2.0,
+        unit: "px",
+    },
+    Parameter {
+        name: "amplitude",
+        kind: ParameterKind::Number,
+        min: 0.0,
+        max: 100.0,
+        default: 4.0,
+        unit: "px",
+    },
+    Parameter {
+        name: "period",
+        kind: ParameterKind::Number,
+        min: 1.0,
+        max: 1000.0,
+        default: 20.0,
+        unit: "px",
+    },
+    Parameter {
+        name: "jitter",
+        kind: ParameterKind::Number,
+        min: 0.0,
+        max: 100.0,
+        default: 0.0,
+        unit: "px",
+    },
+];
+
+impl Default for WavePencil {
+    fn default() -> Self {
+        WavePencil {
+            thickness: SCHEMA[0].default,
+            amplitude: SCHEMA[1].default,
+            period: SCHEMA[2].default,
+            jitter: SCHEMA[3].default,
+            dynamics: Default::default(),
+        }
+    }
 }
 
+/// Tessellated point of a stroke
+type PencilPoint = WithOffset<WithNormal<WithIndex<WithDynamics<Touch>>>>;
+
 impl WavePencil {
-    fn draw_phase_list(
+    /// Points of the curve between touches `range.start` and `range.end`,
+    /// with one more point at each side, so adjacent parts overlap instead of leaving gaps.
+    /// Closed paths are tessellated whole.
+    fn tessellate(
         &self,
-        context: Context,
-        size: (u32, u32),
         path: &TouchList,
-        phases: [f32; 3],
-    ) -> Result<PoolEntry<Texture>, GlError> {
-        let target = context.texture(size, TextureFormat::Rgb, TextureFilter::Nearest)?;
+        range: Range<usize>,
+        state: &DynamicsState,
+    ) -> Vec<PencilPoint> {
+        if path.closed() {
+            return self.tessellate_closed(path, state.seed());
+        }
+        let start = range.start.saturating_sub(STROKE_CONTEXT);
+        let end = (range.end + STROKE_CONTEXT + 1).min(path.len());
+        // Dynamics continue from the state of the stroke, so sensors and random values
+        // don't depend on the part being drawn
+        let mut state = state.clone();
+        state.advance(path, start);
+        let seed = state.seed();
+        let previous = start.checked_sub(1).and_then(|i| path.get(i)).cloned();
+        let touches = WithDynamicsIterator::continued(
+            path[start.min(end)..end].iter().cloned(),
+            previous,
+            &self.dynamics,
+            path.pixel_size(),
+            state,
+        )
+        .zip(start..)
+        .map(|(touch, i)| self.jitter(touch, i, path.pixel_size(), seed));
+
         let points: Vec<_> = WithOffsetIterator::new(WithNormalIterator::new(SplitIterator::new(
-            SmoothIterator::new(SmoothIterator::new(SplitIterator::new(
-                path.iter().map(|v| v.clone()),
+            SmoothIterator::new(SmoothIterator::new(CatmullRomIterator::new(
+                MinDistanceIterator::new(
+                    WithIndexIterator::new(touches, start),
+                    path.pixel_size() * MIN_TOUCH_DISTANCE,
+                ),
                 path.pixel_size(),
             ))),
             1.0,
         )))
         .collect();
 
+        let index = |p: &PencilPoint| p.point.point.index;
+        let first = points
+            .iter()
+            .position(|p| index(p) >= range.start as f32)
+            .unwrap_or(points.len())
+            .saturating_sub(1);
+        let last = points
+            .iter()
+            .rposition(|p| index(p) <= range.end as f32)
+            .map_or(0, |i| (i + 2).min(points.len()));
+        points
+            .into_iter()
+            .skip(first)
+            .take(last.saturating_sub(first))
+            .collect()
+    }
+
+    /// Points of a closed curve, the last point repeats the first one
+    fn tessellate_closed(&self, path: &TouchList, seed: i32) -> Vec<PencilPoint> {
+        let touches = WithDynamicsIterator::new(
+            path.iter().cloned(),
+            &self.dynamics,
+            path.pixel_size(),
+            seed,
+        )
+        .enumerate()
+        .map(|(i, touch)| self.jitter(touch, i, path.pixel_size(), seed));
+        let points: Vec<_> = WithNormalIterator::closed(SplitIterator::closed(
+            SmoothIterator::closed(SmoothIterator::closed(CatmullRomIterator::closed(
+                MinDistanceIterator::new(
+                    WithIndexIterator::new(touches, 0),
+                    path.pixel_size() * MIN_TOUCH_DISTANCE,
+                ),
+                path.pixel_size(),
+            ))),
+            1.0,
+        ))
+        .collect();
+        let first = points.first().cloned();
+        WithOffsetIterator::new(points.into_iter().chain(first)).collect()
+    }
+
+    /// Moves the touch to a random point within `jitter` pixels around it,
+    /// scaled by the jitter dynamics. The generator is seeded by the touch index,
+    /// so every part of a stroke gets the same offsets as the whole stroke.
+    fn jitter(
+        &self,
+        mut touch: WithDynamics<Touch>,
+        index: usize,
+        pixel_size: f32,
+        seed: i32,
+    ) -> WithDynamics<Touch> {
+        let jitter = self.jitter * touch.factor(self.dynamics.index("jitter"));
+        if jitter > 0.0 {
+            let mut random = Random::new(seed ^ (index as i32).wrapping_mul(JITTER_SEED_STEP));
+            touch.point.point = random.in_circle(touch.point.point, jitter * pixel_size);
+        }
+        touch
+    }
+
+    /// Area covered by the points with their width
+    fn bounds(&self, points: &[PencilPoint]) -> Rect {
+        let thickness = self.dynamics.index("thickness");
+        let width = points
+            .iter()
+            .map(|p| self.thickness * p.point.point.point.factor(thickness))
+            .fold(0.0, f32::max);
+        Rect::from_points(points.iter().map(|p| p.point.point.point.point.point))
+            .inflate(width + 1.0)
+    }
+
+    /// Draws over `target` content unless `clear` is set.
+    /// All paths share the depth buffer, so where they cross they join like one stroke.
+    fn draw_phase_list(
+        &self,
+        context: Context,
+        target: &Texture,
+        size: (u32, u32),
+        paths: &[Vec<PencilPoint>],
+        phases: [f32; 3],
+        clear: bool,
+    ) -> Result<(), GlError> {
         let gl = context.gl();
 
         gl.apply(
             Gl::settings()
                 .blend(false)
                 .clear_color(0.0, 0.0, 0.0, 0.0)
-                .frame_buffer(gl.frame_buffer_with_depth(
-                    target.deref().clone(),
-                    gl.depth_buffer(size.0, size.1)?,
-                )?)
+                .frame_buffer(
+                    gl.frame_buffer_with_depth(target.clone(), gl.depth_buffer(size.0, size.1)?)?,
+                )
                 .depth_function(DepthFunction::Less)
                 .clear_depth(1.0)
                 .viewport(0, 0, size.0 as i32, size.1 as i32),
@@ -89,12 +246,14 @@ impl WavePencil {
                     load_glsl!("tools/pencil/phase.f.glsl"),
                     load_glsl!("tools/pencil/phase.v.glsl"),
                 )?;
-                gl.clear_color_buffer();
+                if clear {
+                    gl.clear_color_buffer();
+                }
                 for i in 0..2 {
                     self.draw_phase(
                         context.gl(),
                         &draw_phase_program,
-                        &points,
+                        paths,
                         Vector::new(size.0 as f32, size.1 as f32),
                         phases[i],
                         i as i32,
@@ -102,63 +261,71 @@ impl WavePencil {
                 }
                 Ok(())
             },
-        )?;
-        Ok(target)
+        )
     }
 
     fn draw_phase(
         &self,
         gl: Gl,
         program: &PoolEntry<Program>,
-        path: &Vec<WithOffset<WithNormal<Touch>>>,
+        paths: &[Vec<PencilPoint>],
         resolution: Vector,
         _phase: f32,
         channel: i32,
     ) -> Result<(), GlError> {
-        // wave it here
-        let transformed: Vec<_> = path
-            .iter()
-            .map(|p: &WithOffset<WithNormal<Touch>>| WithOffset {
-                offset: p.offset,
-                point: WithNormal {
-                    normal: p.point.normal,
-                    point: p.point.point.point,
-                },
-            })
-            .collect();
+        let thickness = self.dynamics.index("thickness");
+        let mut points: Vec<DrawPhaseAttributes> = Vec::new();
+        let mut elements: Vec<u32> = Vec::new();
 
-        let points: Vec<DrawPhaseAttributes> = transformed
-            .iter()
-            .flat_map(|point: &WithOffset<WithNormal<Vector>>| {
-                let _offset = point.offset;
-                let normal = point.point.normal;
-                let position = point.point.point;
+        for path in paths {
+            // wave it here
+            let transformed: Vec<_> = path
+                .iter()
+                .map(|p: &PencilPoint| WithOffset {
+                    offset: p.offset,
+                    point: WithNormal {
+                        normal: p.point.normal,
+                        point: WithDynamics {
+                            point: p.point.point.point.point.point,
+                            factors: p.point.point.point.factors.clone(),
+                        },
+                    },
+                })
+                .collect();
 
-                let shift = normal * self.thickness;
+            // Paths share the buffers, so indices are shifted past vertices of the previous paths
+            let base = points.len() as u32;
+            points.extend(transformed.iter().flat_map(
+                |point: &WithOffset<WithNormal<WithDynamics<Vector>>>| {
+                    let _offset = point.offset;
+                    let normal = point.point.normal;
+                    let position = point.point.point.point;
+                    let width = self.thickness * point.point.point.factor(thickness);
 
-                [
-                    DrawPhaseAttributes {
-                        position: position - shift,
-                        offset: -1.0,
-                        width: self.thickness,
-                    },
-                    DrawPhaseAttributes {
-                        position,
-                        offset: 0.0,
-                        width: self.thickness,
-                    },
-                    DrawPhaseAttributes {
-                        position: position + shift,
-                        offset: 1.0,
-                        width: self.thickness,
-                    },
-                ]
-            })
-            .collect();
+                    let shift = normal * width;
 
-        let elements: Vec<u32> = (1..transformed.len())
-            .flat_map(|i| {
-                let p1 = (i * 3) as u32;
+                    [
+                        DrawPhaseAttributes {
+                            position: position - shift,
+                            offset: -1.0,
+                            width,
+                        },
+                        DrawPhaseAttributes {
+                            position,
+                            offset: 0.0,
+                            width,
+                        },
+                        DrawPhaseAttributes {
+                            position: position + shift,
+                            offset: 1.0,
+                            width,
+                        },
+                    ]
+                },
+            ));
+
+            elements.extend((1..transformed.len()).flat_map(|i| {
+                let p1 = base + ((i - 1) * 3) as u32;
                 let p2 = p1 + 3;
 
                 [
@@ -179,8 +346,8 @@ impl WavePencil {
                     p2 + 1,
                     p2 + 2,
                 ]
-            })
-            .collect();
+            }));
+        }
 
         gl.clear_depth_buffer();
         gl.apply(
@@ -199,16 +366,261 @@ impl WavePencil {
 }
 
 impl Tool for WavePencil {
+    fn name(&self) -> &'static str {
+        "wave_pencil"
+    }
+
+    fn schema(&self) -> &'static [Parameter] {
+        &SCHEMA
+    }
+
+    fn parameter(&self, name: &str) -> Option<f32> {
+        match name {
+            "thickness" => Some(self.thickness),
+            "amplitude" => Some(self.amplitude),
+            "period" => Some(self.period),
+            "jitter" => Some(self.jitter),
+            _ => None,
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f32) {
+        match name {
+            "thickness" => self.thickness = value,
+            "amplitude" => self.amplitude = value,
+            "period" => self.period = value,
+            "jitter" => self.jitter = value,
+            _ => {}
+        }
+    }
+
     fn draw(
         &self,
         frame: &mut Frame,
         path: &TouchList,
         _color: Color,
         phases: [f32; 3],
-        _seed: i32,
-    ) -> Result<(), GlError> {
-        let texture = self.draw_phase_list(frame.context(), frame.size(), path, phases)?;
+        seed: i32,
+    ) -> Result<Rect, GlError> {
+        let context = frame.context();
+        let texture = context.texture(frame.size(), TextureFormat::Rgb, TextureFilter::Nearest)?;
+        let points = self.tessellate(
+            path,
+            0..path.len().saturating_sub(1),
+            &DynamicsState::new(seed),
+        );
+        self.draw_phase_list(context, &texture, frame.size(), &[points], phases, true)?;
         frame.replace_texture(texture);
-        Ok(())
+        // The whole texture is replaced, not only the area under the stroke
+        Ok(frame.bounds())
+    }
+
+    fn draw_part(
+        &self,
+        frame: &mut Frame,
+        path: &TouchList,
+        range: Range<usize>,
+        _color: Color,
+        phases: [f32; 3],
+        state: &DynamicsState,
+    ) -> Result<Rect, GlError> {
+        let points = self.tessellate(path, range, state);
+        let bounds = self.bounds(&points);
+        self.draw_phase_list(
+            frame.context(),
+            &frame.texture(),
+            frame.size(),
+            &[points],
+            phases,
+            false,
+        )?;
+        Ok(bounds)
+    }
+
+    fn draw_copies(
+        &self,
+        frame: &mut Frame,
+        paths: &[TouchList],
+        _color: Color,
+        phases: [f32; 3],
+        seed: i32,
+    ) -> Result<Rect, GlError> {
+        let state = DynamicsState::new(seed);
+        let copies: Vec<_> = paths
+            .iter()
+            .map(|path| self.tessellate(path, 0..path.len().saturating_sub(1), &state))
+            .collect();
+        let bounds = copies.iter().fold(Rect::empty(), |rect, points| {
+            rect.union(&self.bounds(points))
+        });
+        self.draw_phase_list(
+            frame.context(),
+            &frame.texture(),
+            frame.size(),
+            &copies,
+            phases,
+            false,
+        )?;
+        Ok(bounds)
+    }
+
+    fn clone_tool(&self) -> Box<dyn Tool> {
+        Box::new(self.clone())
+    }
+
+    fn dynamics(&self) -> &Dynamics {
+        &self.dynamics
+    }
+
+    fn dynamics_mut(&mut self) -> &mut Dynamics {
+        &mut self.dynamics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PencilPoint, WavePencil};
+    use crate::dynamics::{Dynamics, Sensor};
+    use crate::geometry::{Distance, PressureCurve, Touch};
+    use crate::path::{DashPattern, DynamicsState, WithDynamicsIterator};
+    use crate::tool::STROKE_CONTEXT;
+    use crate::touch_list::TouchList;
+
+    #[test]
+    fn parts() {
+        let mut path = TouchList::new(1.0);
+        for i in 0..12 {
+            let t = i as f32 * 0.5;
+            path.push(t * 20.0, t.sin() * 30.0, 0.5);
+        }
+        let pencil = WavePencil {
+            jitter: 2.0,
+            ..Default::default()
+        };
+        let state = DynamicsState::new(1);
+        let full = pencil.tessellate(&path, 0..11, &state);
+        let first = pencil.tessellate(&path, 0..5, &state);
+        let second = pencil.tessellate(&path, 5..11, &state);
+
+        let index = |p: &PencilPoint| p.point.point.index;
+        assert!(index(first.last().unwrap()) >= 5.0);
+        assert!(index(&second[0]) <= 5.0);
+        assert_eq!(index(&full[0]), 0.0);
+        assert_eq!(index(full.last().unwrap()), 11.0);
+
+        // Parts follow the curve of the whole path
+        for p in first.iter().chain(second.iter()) {
+            let position = p.point.point.point.point.point;
+            let nearest = full
+                .iter()
+                .map(|q| q.point.point.point.point.point.distance(position))
+                .fold(f32::INFINITY, f32::min);
+            assert!(nearest < 1.0, "{}", nearest);
+        }
+    }
+
+    #[test]
+    fn dots() {
+        let mut path = TouchList::new(1.0);
+        path.push(0.0, 0.0, 1.0);
+        path.push(10.0, 0.0, 1.0);
+        let pencil = WavePencil::default();
+        let state = DynamicsState::new(1);
+        let dots = DashPattern::new(vec![0.0, 4.0], 0.0, false).apply(&path);
+        assert_eq!(dots.len(), 3);
+        for dot in dots {
+            let points = pencil.tessellate(&dot, 0..1, &state);
+            assert!(points.len() >= 2, "{}", points.len());
+        }
+    }
+
+    #[test]
+    fn jitter() {
+        let mut path = TouchList::new(0.5);
+        for i in 0..20 {
+            path.push(i as f32, 0.0, 0.5);
+        }
+        let pencil = WavePencil {
+            jitter: 4.0,
+            ..Default::default()
+        };
+        let touches = WithDynamicsIterator::new(path.iter().cloned(), &pencil.dynamics, 0.5, 3);
+        let moved: Vec<_> = touches
+            .enumerate()
+            .map(|(i, touch)| {
+                let jittered = pencil.jitter(touch.clone(), i, 0.5, 3);
+                assert_eq!(
+                    jittered.point,
+                    pencil.jitter(touch.clone(), i, 0.5, 3).point
+                );
+                jittered.point.point.distance(touch.point.point)
+            })
+            .collect();
+        // 4 screen pixels are 2 path units
+        assert!(moved.iter().all(|d| *d < 2.0));
+        assert!(moved.iter().any(|d| *d > 0.5));
+        assert_ne!(moved[0], moved[1]);
+
+        // Dynamics scale the jitter, no pressure gives no jitter
+        let mut dynamics = Dynamics::new();
+        let linear = PressureCurve::linear();
+        dynamics.add(Sensor::Pressure, "jitter".into(), 0.0, 1.0, 0.0, &linear);
+        let pencil = WavePencil {
+            jitter: 4.0,
+            dynamics,
+            ..Default::default()
+        };
+        let light = (0..4).map(|i| Touch::new(i as f32, 0.0, 0.0));
+        for (i, touch) in WithDynamicsIterator::new(light, &pencil.dynamics, 0.5, 3).enumerate() {
+            assert_eq!(pencil.jitter(touch.clone(), i, 0.5, 3).point, touch.point);
+        }
+    }
+
+    #[test]
+    fn prefix_independent() {
+        let mut dynamics = Dynamics::new();
+        let linear = PressureCurve::linear();
+        dynamics.add(Sensor::Random, "thickness".into(), 0.5, 1.0, 0.0, &linear);
+        dynamics.add(
+            Sensor::Distance,
+            "thickness".into(),
+            0.5,
+            1.0,
+            300.0,
+            &linear,
+        );
+        let pencil = WavePencil {
+            jitter: 2.0,
+            dynamics,
+            ..Default::default()
+        };
+        let mut path = TouchList::new(1.0);
+        for i in 0..40 {
+            let t = i as f32 * 0.5;
+            path.push(t * 20.0, t.sin() * 30.0, 0.5);
+        }
+        let part = |path: &TouchList, state: &DynamicsState| {
+            let points: Vec<(Touch, Vec<f32>)> = pencil
+                .tessellate(path, 30..33, state)
+                .iter()
+                .map(|p| {
+                    (
+                        p.point.point.point.point,
+                        p.point.point.point.factors.clone(),
+                    )
+                })
+                .collect();
+            points
+        };
+        let expected = part(&path, &DynamicsState::new(2));
+
+        let mut state = DynamicsState::new(2);
+        state.advance(&path, 30 - STROKE_CONTEXT);
+        // Touches before the part context are never read, so garbage there changes nothing
+        let mut broken = path.clone();
+        for touch in broken[..30 - STROKE_CONTEXT - 1].iter_mut() {
+            *touch = Touch::new(f32::NAN, f32::NAN, f32::NAN);
+        }
+        assert_eq!(part(&broken, &state), expected);
     }
 }
@@ -1,4 +1,4 @@
-use crate::geometry::Touch;
+use crate::geometry::{Distance, PressureCurve, Touch, Transform};
 use std::ops::{Deref, DerefMut};
 use wasm_bindgen::prelude::*;
 
@@ -7,6 +7,18 @@ use wasm_bindgen::prelude::*;
 pub struct TouchList {
     pixel_size: f32,
     points: Vec<Touch>,
+    /// Applied to pressure of pushed touches
+    pressure_curve: PressureCurve,
+    /// Pushed touches closer to the previous one, in screen pixels, are dropped
+    min_distance: f32,
+    /// Pushed touches sooner after the previous one, in milliseconds, are dropped
+    min_interval: f64,
+    /// Time of the last touch pushed with `push_at`
+    last_time: Option<f64>,
+    /// Times of the touches in milliseconds, valid while every touch came with `push_at`
+    times: Vec<f64>,
+    /// The last touch connects to the first one, which isn't repeated at the end
+    closed: bool,
 }
 
 #[wasm_bindgen]
@@ -16,6 +28,7 @@ impl TouchList {
         Self {
             pixel_size,
             points: Default::default(),
+            ..Default::default()
         }
     }
 
@@ -23,6 +36,7 @@ impl TouchList {
         Self {
             pixel_size,
             points: Vec::with_capacity(capacity),
+            ..Default::default()
         }
     }
 
@@ -31,12 +45,133 @@ impl TouchList {
     }
 
     pub fn push(&mut self, x: f32, y: f32, pressure: f32) {
-        self.points.push(Touch::new(x, y, pressure));
+        self.push_touch(Touch::new(x, y, pressure), None);
+    }
+
+    /// @param tilt is 0 when the pen is perpendicular to the surface, 1 when it lies flat
+    pub fn push_with_tilt(&mut self, x: f32, y: f32, pressure: f32, tilt: f32) {
+        self.push_touch(
+            Touch::new(x, y, pressure).with_tilt(tilt.clamp(0.0, 1.0)),
+            None,
+        );
+    }
+
+    /// Same as `push_with_tilt`, the time is checked against the minimal interval,
+    /// returns false if the touch is filtered out
+    /// @param time of the event in milliseconds, like `PointerEvent.timeStamp`
+    pub fn push_at(&mut self, x: f32, y: f32, pressure: f32, tilt: f32, time: f64) -> bool {
+        self.push_touch(
+            Touch::new(x, y, pressure).with_tilt(tilt.clamp(0.0, 1.0)),
+            Some(time),
+        )
+    }
+
+    /// Drops pushed touches closer than `min_distance` screen pixels
+    /// or sooner than `min_interval` milliseconds after the previous touch,
+    /// noisy input gives touches at almost the same position which make degenerate segments
+    pub fn set_input_filter(&mut self, min_distance: f32, min_interval: f64) {
+        self.min_distance = min_distance.max(0.0);
+        self.min_interval = min_interval.max(0.0);
+    }
+
+    /// The curve is applied to touches pushed after the call
+    pub fn set_pressure_curve(&mut self, curve: &PressureCurve) {
+        self.pressure_curve = curve.clone();
     }
 
     pub fn pixel_size(&self) -> f32 {
         self.pixel_size
     }
+
+    pub fn set_pixel_size(&mut self, pixel_size: f32) {
+        self.pixel_size = pixel_size;
+    }
+
+    pub fn closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Closed paths are drawn whole, with the segment from the last touch to the first one
+    pub fn set_closed(&mut self, closed: bool) {
+        self.closed = closed;
+    }
+
+    /// @param matrix is an array [a, b, c, d, e, f], same as canvas setTransform
+    pub fn transform(&self, matrix: Vec<f32>) -> TouchList {
+        assert_eq!(matrix.len(), 6);
+        self.transformed(&Transform::new(
+            matrix[0], matrix[1], matrix[2], matrix[3], matrix[4], matrix[5],
+        ))
+    }
+}
+
+impl TouchList {
+    fn push_touch(&mut self, touch: Touch, time: Option<f64>) -> bool {
+        if let Some(last) = self.points.last() {
+            if last.distance(touch) < self.min_distance * self.pixel_size {
+                return false;
+            }
+            if let (Some(time), Some(last_time)) = (time, self.last_time) {
+                if time - last_time < self.min_interval {
+                    return false;
+                }
+            }
+        }
+        if time.is_some() {
+            self.last_time = time;
+        }
+        self.times.truncate(self.points.len());
+        match time {
+            Some(time) if self.times.len() == self.points.len() => self.times.push(time),
+            _ => {}
+        }
+        self.points.push(Touch {
+            pressure: self.pressure_curve.apply(touch.pressure),
+            ..touch
+        });
+        true
+    }
+
+    /// Times of the touches in milliseconds, `None` unless all of them were pushed with `push_at`
+    pub fn times(&self) -> Option<&[f64]> {
+        if self.times.len() == self.points.len() {
+            Some(&self.times)
+        } else {
+            None
+        }
+    }
+
+    /// Appends touches, with `times` of them if the touches of the list have times too
+    pub fn extend_timed(
+        &mut self,
+        touches: impl IntoIterator<Item = Touch>,
+        times: Option<&[f64]>,
+    ) {
+        let start = self.points.len();
+        self.times.truncate(start);
+        self.points.extend(touches);
+        match times {
+            Some(times)
+                if self.times.len() == start && times.len() == self.points.len() - start =>
+            {
+                self.times.extend_from_slice(times)
+            }
+            _ => {}
+        }
+    }
+
+    pub fn transformed(&self, transform: &Transform) -> TouchList {
+        TouchList {
+            pixel_size: self.pixel_size * transform.scale_factor(),
+            points: self.points.iter().map(|t| *transform * *t).collect(),
+            pressure_curve: self.pressure_curve.clone(),
+            min_distance: self.min_distance,
+            min_interval: self.min_interval,
+            last_time: self.last_time,
+            times: self.times.clone(),
+            closed: self.closed,
+        }
+    }
 }
 
 impl Deref for TouchList {
@@ -52,3 +187,47 @@ impl DerefMut for TouchList {
         &mut self.points
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::TouchList;
+
+    #[test]
+    fn input_filter() {
+        let mut path = TouchList::new(0.5);
+        path.set_input_filter(2.0, 10.0);
+        assert!(path.push_at(0.0, 0.0, 0.5, 0.0, 0.0));
+        // Closer than 2 screen pixels
+        assert!(!path.push_at(0.5, 0.5, 0.5, 0.0, 20.0));
+        // Too soon
+        assert!(!path.push_at(5.0, 0.0, 0.5, 0.0, 5.0));
+        assert!(path.push_at(5.0, 0.0, 0.5, 0.0, 15.0));
+        path.push(5.5, 0.0, 0.5);
+        path.push(6.0, 0.0, 0.5);
+        assert_eq!(path.len(), 3);
+        assert_eq!(path[2].x(), 6.0);
+
+        let transformed = path.transform(vec![2.0, 0.0, 0.0, 2.0, 0.0, 0.0]);
+        assert_eq!(transformed.pixel_size(), 1.0);
+    }
+
+    #[test]
+    fn times() {
+        let mut path = TouchList::new(1.0);
+        path.push_at(0.0, 0.0, 0.5, 0.0, 10.0);
+        path.push_at(1.0, 0.0, 0.5, 0.0, 18.0);
+        assert_eq!(path.times(), Some(&[10.0, 18.0][..]));
+
+        // Touches added past the times are dropped with them
+        path.push(2.0, 0.0, 0.5);
+        assert_eq!(path.times(), None);
+        path.truncate(2);
+        assert_eq!(path.times(), Some(&[10.0, 18.0][..]));
+
+        let mut stroke = TouchList::new(1.0);
+        stroke.extend_timed(path.iter().cloned(), path.times());
+        assert_eq!(stroke.times(), path.times());
+        stroke.extend_timed(path.iter().cloned(), None);
+        assert_eq!(stroke.times(), None);
+    }
+}
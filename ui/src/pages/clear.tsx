@@ -1,6 +1,7 @@
 import * as React from "react";
 import {Form, Slider} from "antd";
 import {InputProps, withCanvas} from "./with-canvas";
+import {color_from_srgb} from "../../../pkg";
 
 function CleanComponent({value, onChange}: InputProps<{ x: number, y: number, w: number, h: number }>) {
     return <Form>
@@ -24,11 +25,14 @@ export const ClearPage = withCanvas(
     CleanComponent,
     (context) => {
         const frame = context.frame_with_size(100, 100);
-        frame.clear(0.3, 0.3, 0, 1);
+        // Frames and the canvas take premultiplied linear colors
+        const [r, g, b, a] = color_from_srgb(new Float32Array([0.3, 0.3, 0, 1]));
+        frame.clear(r, g, b, a);
+        const background = color_from_srgb(new Float32Array([0, 0.3, 0.3, 1]));
         return ({x, y, w, h}, width, height) => {
             context.clear(
                 new Int32Array([0, 0, width, height]),
-                new Float32Array([0, 0.3, 0.3, 1])
+                background
             );
 
             context.draw_frame_1(frame, new Int32Array([width * x / 1000, height * y / 1000, width * w / 1000, height * h / 1000]));